@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS exports (
+    id INTEGER PRIMARY KEY NOT NULL,
+    exported_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    path TEXT NOT NULL,
+    template TEXT NOT NULL,
+    -- ExportKind as JSON, '{}' is a template export with default image options
+    options TEXT NOT NULL DEFAULT '{}',
+
+    manga_group INTEGER NOT NULL,
+    FOREIGN KEY(manga_group) REFERENCES manga_groups(id)
+);
@@ -0,0 +1,2 @@
+-- Folder or archive the entry was imported from, to pick pages from again
+ALTER TABLE manga_entries ADD COLUMN source_path TEXT;
@@ -0,0 +1,5 @@
+-- Folder the group entries were added from, to sync the group with it later
+ALTER TABLE manga_groups ADD COLUMN source_folder TEXT;
+
+-- Set when the source folder of the entry vanished during a sync
+ALTER TABLE manga_entries ADD COLUMN missing_from_source BOOLEAN NOT NULL DEFAULT FALSE;
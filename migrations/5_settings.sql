@@ -0,0 +1,5 @@
+-- Application settings, values are JSON
+CREATE TABLE IF NOT EXISTS settings (
+    name TEXT PRIMARY KEY NOT NULL,
+    value TEXT NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Set for pages taken from the entry source, only those are replaced when re-picking
+ALTER TABLE manga_images ADD COLUMN from_source BOOLEAN NOT NULL DEFAULT FALSE;
@@ -5,6 +5,8 @@ pub const THUMBNAIL_IMAGE_HEIGHT: u32 = 72;
 pub struct MangaGroup {
     pub added_on: chrono::NaiveDateTime,
     pub id: i64,
+    /// Folder the entries were added from, for syncing the group with it.
+    pub source_folder: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -14,6 +16,21 @@ pub struct MangaEntry {
     pub comment: String,
     pub manga_group: i64,
     pub id: i64,
+    /// Folder or archive the entry was imported from.
+    pub source_path: Option<String>,
+    /// Source folder of the entry vanished during the last sync.
+    pub missing_from_source: bool,
+}
+
+/// Past export of a group, kept so it can be repeated with the same settings.
+#[derive(Debug, Clone)]
+pub struct MangaExport {
+    pub id: i64,
+    pub exported_on: chrono::NaiveDateTime,
+    pub path: String,
+    pub template: String,
+    pub options: String,
+    pub manga_group: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -21,6 +38,8 @@ pub struct MangaImage {
     pub path: String,
     pub manga: i64,
     pub id: i64,
+    /// Page taken from the entry source, replaced when pages are re-picked.
+    pub from_source: bool,
 }
 
 pub struct DisplayedMangaImage {
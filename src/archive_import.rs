@@ -0,0 +1,121 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+use rayon::prelude::*;
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::image_ingest::IngestImage;
+use shared::types::MangaEntry;
+
+/// Extensions offered in the file dialog. RAR based CBR files aren't supported,
+/// since reading them needs the non-free `unrar` library.
+pub const ARCHIVE_EXTENSIONS: [&str; 2] = ["cbz", "zip"];
+
+pub struct ArchivePage {
+    /// Path of the page inside the archive.
+    pub name: String,
+    pub thumbnail: egui::ImageData,
+}
+
+impl core::fmt::Debug for ArchivePage {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct("ArchivePage")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// Pages of an archive, offered to be added to `entry`.
+#[derive(Debug)]
+pub struct ArchivePages {
+    pub entry: MangaEntry,
+    pub path: PathBuf,
+    pub pages: Vec<ArchivePage>,
+    /// Pages that can't be decoded, with the reason.
+    pub skipped: Vec<(String, String)>,
+}
+
+fn open_archive(path: &Path) -> AnyResult<zip::ZipArchive<std::fs::File>> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open '{}'.", path.display()))?;
+    zip::ZipArchive::new(file).with_context(|| format!("'{}' isn't a ZIP file.", path.display()))
+}
+
+fn read_file(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> AnyResult<Vec<u8>> {
+    let mut file = archive
+        .by_name(name)
+        .with_context(|| format!("'{name}' isn't in the archive."))?;
+    let mut contents = Vec::with_capacity(usize::try_from(file.size()).unwrap_or_default());
+    file.read_to_end(&mut contents)
+        .with_context(|| format!("Failed to extract '{name}'."))?;
+    Ok(contents)
+}
+
+/// Names of the images in an archive, in natural order.
+pub fn list_pages(path: &Path) -> AnyResult<Vec<String>> {
+    let archive = open_archive(path)?;
+    let mut names = archive
+        .file_names()
+        .filter(|name| !name.ends_with('/') && !name.starts_with("__MACOSX/"))
+        .filter(|name| image::ImageFormat::from_path(name).is_ok_and(|x| x.reading_enabled()))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    names.sort_by(|a, b| natord::compare(a, b));
+    Ok(names)
+}
+
+/// Reads every page of the archive and makes its thumbnail,
+/// pages that can't be decoded are skipped.
+pub fn load_pages(entry: MangaEntry, path: PathBuf) -> AnyResult<ArchivePages> {
+    let names = list_pages(&path)?;
+    if names.is_empty() {
+        anyhow::bail!("No images found in '{}'.", path.display());
+    }
+
+    let mut archive = open_archive(&path)?;
+    let contents = names
+        .iter()
+        .map(|name| read_file(&mut archive, name))
+        .collect::<AnyResult<Vec<_>>>()?;
+
+    let thumbnails = core::iter::zip(names, contents)
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|(name, contents)| {
+            let thumbnail = crate::data_storage::ImageCache::create_thumbnail(&contents);
+            (name, thumbnail)
+        })
+        .collect::<Vec<_>>();
+
+    let mut pages = Vec::with_capacity(thumbnails.len());
+    let mut skipped = Vec::new();
+    for (name, thumbnail) in thumbnails {
+        match thumbnail {
+            Ok(thumbnail) => pages.push(ArchivePage { name, thumbnail }),
+            Err(error) => skipped.push((name, error.to_string())),
+        }
+    }
+    if pages.is_empty() {
+        anyhow::bail!("None of the images in '{}' can be decoded.", path.display());
+    }
+
+    Ok(ArchivePages {
+        entry,
+        path,
+        pages,
+        skipped,
+    })
+}
+
+/// Extracts the selected pages of an archive, along with their names.
+pub fn read_pages(path: &Path, names: &[String]) -> AnyResult<Vec<(String, IngestImage)>> {
+    let mut archive = open_archive(path)?;
+    names
+        .iter()
+        .map(|name| {
+            let contents = read_file(&mut archive, name)?;
+            Ok((name.clone(), IngestImage::file(contents, name)))
+        })
+        .collect()
+}
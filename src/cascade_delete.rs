@@ -23,6 +23,11 @@ impl CascadeDelete for MangaGroup {
             entry.delete_cascade(db).await;
         }
 
+        sqlx::query!(r"DELETE FROM exports WHERE manga_group = ?", self.id)
+            .execute(db)
+            .await
+            .unwrap();
+
         sqlx::query!(r"DELETE FROM manga_groups WHERE id = ?", self.id)
             .execute(db)
             .await
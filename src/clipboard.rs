@@ -0,0 +1,278 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+
+use std::path::PathBuf;
+
+/// Path of an image file pasted instead of the clipboard contents.
+pub const MOCK_CLIPBOARD_VARIABLE: &str = "MOCK_CLIPBOARD_FILE";
+
+/// Cross-platform access to the system clipboard.
+///
+/// On Linux the copied contents are served by this object, so it should be
+/// kept alive for as long as the copied text is expected to be pasted.
+pub struct Clipboard {
+    inner: arboard::Clipboard,
+}
+
+impl Clipboard {
+    pub fn new() -> AnyResult<Self> {
+        Ok(Self {
+            inner: arboard::Clipboard::new().context("Failed to open clipboard.")?,
+        })
+    }
+
+    pub fn set_text(&mut self, text: &str) -> AnyResult<()> {
+        self.inner
+            .set_text(text)
+            .context("Failed to copy text to clipboard.")
+    }
+}
+
+/// Clipboard contents that can be turned into images.
+#[derive(Debug)]
+pub enum ClipboardContents {
+    /// Image file as it would be stored on disk, e.g. PNG or BMP.
+    Encoded(Vec<u8>),
+    /// Already decoded RGBA pixels.
+    Rgba {
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    },
+    /// Files copied in a file manager.
+    Files(Vec<PathBuf>),
+}
+
+/// Source of pasted images, implemented per platform.
+pub trait ImageClipboard {
+    fn read_contents(&mut self) -> AnyResult<ClipboardContents>;
+}
+
+/// Opens the clipboard of the current platform.
+///
+/// Set `MOCK_CLIPBOARD_FILE` to the path of an image to paste it instead,
+/// which works without a display server.
+pub fn open_image_clipboard() -> AnyResult<Box<dyn ImageClipboard>> {
+    if let Ok(path) = std::env::var(MOCK_CLIPBOARD_VARIABLE) {
+        let contents = std::fs::read(&path)
+            .with_context(|| format!("Failed to read mock clipboard file '{path}'."))?;
+        return Ok(Box::new(MockClipboard::new(ClipboardContents::Encoded(
+            contents,
+        ))));
+    }
+
+    #[cfg(windows)]
+    return Ok(Box::new(WindowsClipboard));
+
+    #[cfg(not(windows))]
+    return Ok(Box::new(Clipboard::new()?));
+}
+
+/// Reads the clipboard and decodes every image found in it.
+pub fn read_images(clipboard: &mut dyn ImageClipboard) -> AnyResult<Vec<image::DynamicImage>> {
+    match clipboard.read_contents()? {
+        ClipboardContents::Encoded(contents) => {
+            let image = image::ImageReader::new(std::io::Cursor::new(contents))
+                .with_guessed_format()?
+                .decode()
+                .context("Failed to decode image from clipboard.")?;
+            Ok(vec![image])
+        }
+        ClipboardContents::Rgba {
+            width,
+            height,
+            pixels,
+        } => {
+            let image = image::RgbaImage::from_raw(width, height, pixels)
+                .context("Clipboard image has wrong size.")?;
+            Ok(vec![image::DynamicImage::ImageRgba8(image)])
+        }
+        ClipboardContents::Files(paths) => {
+            let images = paths
+                .iter()
+                .filter(|path| image::ImageFormat::from_path(path).is_ok())
+                .map(|path| {
+                    image::open(path)
+                        .with_context(|| format!("Failed to open '{}'.", path.display()))
+                })
+                .collect::<AnyResult<Vec<_>>>()?;
+            if images.is_empty() {
+                anyhow::bail!("None of the copied files are images.");
+            }
+            Ok(images)
+        }
+    }
+}
+
+/// Clipboard with fixed contents, which can be pasted once.
+pub struct MockClipboard {
+    contents: Option<ClipboardContents>,
+}
+
+impl MockClipboard {
+    pub fn new(contents: ClipboardContents) -> Self {
+        Self {
+            contents: Some(contents),
+        }
+    }
+}
+
+impl ImageClipboard for MockClipboard {
+    fn read_contents(&mut self) -> AnyResult<ClipboardContents> {
+        self.contents.take().context("Clipboard is empty.")
+    }
+}
+
+/// X11, Wayland (with the data control protocol) and macOS through `arboard`.
+#[cfg(not(windows))]
+impl ImageClipboard for Clipboard {
+    fn read_contents(&mut self) -> AnyResult<ClipboardContents> {
+        match self.inner.get_image() {
+            Ok(image) => {
+                return Ok(ClipboardContents::Rgba {
+                    width: u32::try_from(image.width)?,
+                    height: u32::try_from(image.height)?,
+                    pixels: image.bytes.into_owned(),
+                });
+            }
+            Err(arboard::Error::ContentNotAvailable) => {}
+            Err(error) => return Err(error).context("Failed to read image from clipboard."),
+        }
+
+        match self.inner.get().file_list() {
+            Ok(files) if !files.is_empty() => Ok(ClipboardContents::Files(files)),
+            Ok(_) | Err(arboard::Error::ContentNotAvailable) => {
+                anyhow::bail!("Clipboard has no images or files.")
+            }
+            Err(error) => Err(error).context("Failed to read files from clipboard."),
+        }
+    }
+}
+
+/// Prefers PNG put by browsers and editors, then copied files, then plain bitmaps.
+#[cfg(windows)]
+pub struct WindowsClipboard;
+
+#[cfg(windows)]
+impl ImageClipboard for WindowsClipboard {
+    fn read_contents(&mut self) -> AnyResult<ClipboardContents> {
+        use clipboard_win::Getter;
+
+        let _clip = clipboard_win::Clipboard::new_attempts(10)
+            .map_err(|error| anyhow::anyhow!("Failed to open clipboard: {error}"))?;
+
+        if let Some(png_format) = clipboard_win::register_format("PNG")
+            && clipboard_win::is_format_avail(png_format.get())
+        {
+            let mut buffer = Vec::with_capacity(500_000);
+            clipboard_win::formats::RawData(png_format.get())
+                .read_clipboard(&mut buffer)
+                .map_err(|error| anyhow::anyhow!("Failed to read PNG from clipboard: {error}"))?;
+            return Ok(ClipboardContents::Encoded(buffer));
+        }
+
+        if clipboard_win::is_format_avail(clipboard_win::formats::CF_HDROP) {
+            let mut files: Vec<PathBuf> = Vec::new();
+            clipboard_win::formats::FileList
+                .read_clipboard(&mut files)
+                .map_err(|error| anyhow::anyhow!("Failed to read files from clipboard: {error}"))?;
+            return Ok(ClipboardContents::Files(files));
+        }
+
+        if clipboard_win::is_format_avail(clipboard_win::formats::CF_DIB) {
+            let mut buffer = Vec::with_capacity(500_000);
+            clipboard_win::formats::Bitmap
+                .read_clipboard(&mut buffer)
+                .map_err(|error| {
+                    anyhow::anyhow!("Failed to read bitmap from clipboard: {error}")
+                })?;
+            return Ok(ClipboardContents::Encoded(buffer));
+        }
+
+        anyhow::bail!("Clipboard has no images or files.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(format: image::ImageFormat) -> Vec<u8> {
+        let image = image::RgbImage::from_pixel(3, 2, image::Rgb([10, 20, 30]));
+        let mut contents = Vec::new();
+        image
+            .write_to(&mut std::io::Cursor::new(&mut contents), format)
+            .unwrap();
+        contents
+    }
+
+    fn paste(contents: ClipboardContents) -> AnyResult<Vec<image::DynamicImage>> {
+        read_images(&mut MockClipboard::new(contents))
+    }
+
+    #[test]
+    fn pastes_encoded_png() {
+        let images = paste(ClipboardContents::Encoded(encode(image::ImageFormat::Png))).unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!((images[0].width(), images[0].height()), (3, 2));
+    }
+
+    #[test]
+    fn pastes_encoded_bmp() {
+        let images = paste(ClipboardContents::Encoded(encode(image::ImageFormat::Bmp))).unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(
+            images[0].to_rgb8().get_pixel(2, 1),
+            &image::Rgb([10, 20, 30])
+        );
+    }
+
+    #[test]
+    fn pastes_rgba_pixels() {
+        let images = paste(ClipboardContents::Rgba {
+            width: 2,
+            height: 2,
+            pixels: vec![255; 16],
+        })
+        .unwrap();
+        assert_eq!((images[0].width(), images[0].height()), (2, 2));
+    }
+
+    #[test]
+    fn rejects_rgba_with_wrong_size() {
+        let result = paste(ClipboardContents::Rgba {
+            width: 2,
+            height: 2,
+            pixels: vec![255; 15],
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn pastes_images_from_file_list() {
+        let folder = std::env::temp_dir().join(format!("clipboard_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let image_path = folder.join("page.png");
+        let text_path = folder.join("notes.txt");
+        std::fs::write(&image_path, encode(image::ImageFormat::Png)).unwrap();
+        std::fs::write(&text_path, "not an image").unwrap();
+
+        let images = paste(ClipboardContents::Files(vec![
+            text_path.clone(),
+            image_path,
+        ]));
+        let only_text = paste(ClipboardContents::Files(vec![text_path]));
+        std::fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(images.unwrap().len(), 1);
+        assert!(only_text.is_err());
+    }
+
+    #[test]
+    fn mock_is_empty_after_paste() {
+        let mut clipboard =
+            MockClipboard::new(ClipboardContents::Encoded(encode(image::ImageFormat::Png)));
+        assert!(read_images(&mut clipboard).is_ok());
+        assert!(read_images(&mut clipboard).is_err());
+    }
+}
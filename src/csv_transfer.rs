@@ -0,0 +1,181 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::types::SCORE_RANGE;
+use shared::types::{MangaEntry, MangaImage};
+
+/// A single row of the CSV file. `id` is empty for rows that should create new entries;
+/// `image_count` is informational and ignored on import.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CsvEntryRow {
+    pub id: Option<i64>,
+    pub group: i64,
+    pub name: String,
+    pub score: i64,
+    pub comment: String,
+    #[serde(default)]
+    pub image_count: Option<usize>,
+}
+
+/// Change that applying a CSV file would make to the database.
+#[derive(Debug, Clone)]
+pub enum CsvImportChange {
+    Update {
+        before: MangaEntry,
+        after: MangaEntry,
+    },
+    Create(MangaEntry),
+    Skip {
+        line: usize,
+        reason: String,
+    },
+}
+
+impl CsvImportChange {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Update { before, after } => {
+                let mut changes = Vec::with_capacity(3);
+                if before.name != after.name {
+                    changes.push(format!("name '{}' → '{}'", before.name, after.name));
+                }
+                if before.score != after.score {
+                    changes.push(format!("score {} → {}", before.score, after.score));
+                }
+                if before.comment != after.comment {
+                    changes.push("comment changed".to_string());
+                }
+                format!("Update #{:03}: {}", before.id, changes.join(", "))
+            }
+            Self::Create(entry) => format!(
+                "Create in group #{:03}: '{}' ({}/10)",
+                entry.manga_group, entry.name, entry.score
+            ),
+            Self::Skip { line, reason } => format!("Skip line {line}: {reason}"),
+        }
+    }
+}
+
+pub fn write_entries(path: &Path, entries: &[(MangaEntry, Vec<MangaImage>)]) -> AnyResult<()> {
+    let mut writer = csv::Writer::from_path(path)
+        .with_context(|| format!("Failed to create '{}'.", path.display()))?;
+    for (entry, images) in entries {
+        writer.serialize(CsvEntryRow {
+            id: Some(entry.id),
+            group: entry.manga_group,
+            name: entry.name.clone(),
+            score: entry.score,
+            comment: entry.comment.clone(),
+            image_count: Some(images.len()),
+        })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the CSV file and compares it with the current entries without changing anything.
+///
+/// Rows are matched with existing entries by id; rows without an id or with an unknown one
+/// are matched by name within their group, and only create new entries when no entry has
+/// that name. The group of existing entries is never changed, rows
+/// moving them to another group are skipped, as are rows setting a score outside 1..=10.
+pub fn plan_import(
+    path: &Path,
+    existing_entries: &[MangaEntry],
+    existing_groups: &HashSet<i64>,
+) -> AnyResult<Vec<CsvImportChange>> {
+    let mut reader = csv::Reader::from_path(path)
+        .with_context(|| format!("Failed to open '{}'.", path.display()))?;
+    let entries_by_id: HashMap<i64, &MangaEntry> =
+        existing_entries.iter().map(|x| (x.id, x)).collect();
+
+    let mut result = Vec::new();
+    for (index, row) in reader.deserialize::<CsvEntryRow>().enumerate() {
+        // Line 1 is the header
+        let line = index + 2;
+        let row = match row {
+            Ok(row) => row,
+            Err(error) => {
+                result.push(CsvImportChange::Skip {
+                    line,
+                    reason: error.to_string(),
+                });
+                continue;
+            }
+        };
+
+        if !existing_groups.contains(&row.group) {
+            result.push(CsvImportChange::Skip {
+                line,
+                reason: format!("group #{} doesn't exist", row.group),
+            });
+            continue;
+        }
+
+        let before = row
+            .id
+            .and_then(|id| entries_by_id.get(&id).copied())
+            .or_else(|| {
+                existing_entries
+                    .iter()
+                    .find(|x| x.manga_group == row.group && x.name == row.name)
+            });
+        // Unscored entries are exported with their score of 0, which is kept as it is
+        if !SCORE_RANGE.contains(&row.score) && before.is_none_or(|x| x.score != row.score) {
+            result.push(CsvImportChange::Skip {
+                line,
+                reason: format!(
+                    "score {} is outside of {}..={}",
+                    row.score,
+                    SCORE_RANGE.start(),
+                    SCORE_RANGE.end()
+                ),
+            });
+            continue;
+        }
+
+        match before {
+            Some(before) if before.manga_group != row.group => {
+                result.push(CsvImportChange::Skip {
+                    line,
+                    reason: format!(
+                        "entry #{:03} is in group #{:03}, moving it to group #{:03} isn't supported",
+                        before.id, before.manga_group, row.group
+                    ),
+                });
+            }
+            Some(before) => {
+                let after = MangaEntry {
+                    name: row.name,
+                    score: row.score,
+                    comment: row.comment,
+                    ..(*before).clone()
+                };
+                if before.name != after.name
+                    || before.score != after.score
+                    || before.comment != after.comment
+                {
+                    result.push(CsvImportChange::Update {
+                        before: (*before).clone(),
+                        after,
+                    });
+                }
+            }
+            None => result.push(CsvImportChange::Create(MangaEntry {
+                name: row.name,
+                score: row.score,
+                comment: row.comment,
+                manga_group: row.group,
+                id: 0,
+                source_path: None,
+                missing_from_source: false,
+            })),
+        }
+    }
+
+    Ok(result)
+}
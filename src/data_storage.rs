@@ -1,13 +1,26 @@
 use anyhow::Context;
+use anyhow::Result as AnyResult;
 
 use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::cascade_delete::CascadeDelete;
+use crate::csv_transfer::CsvImportChange;
+use crate::export_common::{ExportImageOptions, ExportKind};
+use crate::export_lint::LintAction;
+use crate::folder_names::{FolderNameReview, NameCleanupRules};
+use crate::folder_sync::{FolderSyncPlan, SyncChange, VanishedAction};
+use crate::html_import::ImportedReview;
+use crate::image_export::ImageExportOptions;
+use crate::image_ingest::{IngestImage, IngestSettings};
+use crate::inbox::{InboxSettings, InboxWatcher};
 use crate::manga_ui::MangaUI;
+use crate::site_export::SiteLayout;
+use crate::source_import::SourceImportOptions;
+use crate::text_export::TextExportFormat;
 use crate::types::{BackendChannelSend, BackendCommand, GuiChannelRecv, GuiCommand, SqlitePool};
 use shared::types::{
-    DisplayedMangaEntry, DisplayedMangaImage, MangaEntry, MangaGroup, MangaImage,
+    DisplayedMangaEntry, DisplayedMangaImage, MangaEntry, MangaExport, MangaGroup, MangaImage,
     THUMBNAIL_IMAGE_HEIGHT, THUMBNAIL_IMAGE_WIDTH,
 };
 
@@ -31,27 +44,29 @@ impl ImageCache {
 
         self.thumbnails_cache
             .entry(image.id)
-            .or_insert_with(|| {
-                let original_image = image::load_from_memory(&file_contents).unwrap();
-                let resized_image = original_image.resize(
-                    THUMBNAIL_IMAGE_WIDTH,
-                    THUMBNAIL_IMAGE_HEIGHT,
-                    image::imageops::FilterType::Lanczos3,
-                );
-                let image_buffer = resized_image.to_rgba8();
-
-                egui::ColorImage::from_rgba_unmultiplied(
-                    [
-                        usize::try_from(resized_image.width()).unwrap(),
-                        usize::try_from(resized_image.height()).unwrap(),
-                    ],
-                    image_buffer.as_flat_samples().as_slice(),
-                )
-                .into()
-            })
+            .or_insert_with(|| Self::create_thumbnail(&file_contents).unwrap())
             .clone()
     }
 
+    pub fn create_thumbnail(file_contents: &[u8]) -> AnyResult<egui::ImageData> {
+        let original_image = image::load_from_memory(file_contents)?;
+        let resized_image = original_image.resize(
+            THUMBNAIL_IMAGE_WIDTH,
+            THUMBNAIL_IMAGE_HEIGHT,
+            image::imageops::FilterType::Lanczos3,
+        );
+        let image_buffer = resized_image.to_rgba8();
+
+        Ok(egui::ColorImage::from_rgba_unmultiplied(
+            [
+                usize::try_from(resized_image.width())?,
+                usize::try_from(resized_image.height())?,
+            ],
+            image_buffer.as_flat_samples().as_slice(),
+        )
+        .into())
+    }
+
     fn get_image_data(&mut self, image: &MangaImage) -> DisplayedMangaImage {
         DisplayedMangaImage {
             image: image.clone(),
@@ -60,11 +75,15 @@ impl ImageCache {
     }
 
     fn remove_image(&mut self, image: &MangaImage) {
-        self.images_cache.remove(&image.id).unwrap();
-        self.thumbnails_cache.remove(&image.id).unwrap();
+        self.images_cache.remove(&image.id);
+        self.thumbnails_cache.remove(&image.id);
     }
 }
 
+pub fn is_supported_image(path: &std::path::Path) -> bool {
+    image::ImageFormat::from_path(path).is_ok_and(crate::image_ingest::is_readable)
+}
+
 pub struct DataStorage {
     pub manga_groups: Vec<MangaGroup>,
     pub selected_group: Option<MangaGroup>,
@@ -74,6 +93,11 @@ pub struct DataStorage {
     pub backend_send: BackendChannelSend,
     pub gui_recv: GuiChannelRecv,
     pub exiting: bool,
+    pub ingest_settings: IngestSettings,
+    pub inbox_settings: InboxSettings,
+    pub inbox_watcher: Option<InboxWatcher>,
+    /// Entry that new inbox files go straight into, they wait in the tray without it.
+    pub inbox_target: Option<MangaEntry>,
 }
 
 impl DataStorage {
@@ -84,6 +108,14 @@ impl DataStorage {
     pub async fn run(mut self) {
         self.update_manga_groups().await;
         self.send_updated_manga_groups();
+        self.send_export_templates();
+        self.ingest_settings = self.load_setting(crate::image_ingest::SETTING_NAME).await;
+        self.backend_send
+            .send(BackendCommand::UpdateIngestSettings(self.ingest_settings))
+            .unwrap();
+        self.inbox_settings = self.load_setting(crate::inbox::SETTING_NAME).await;
+        let result = self.start_inbox();
+        self.send_error_if_failed(result);
 
         loop {
             self.process_gui_commands().await;
@@ -91,6 +123,9 @@ impl DataStorage {
             if self.exiting {
                 break;
             }
+
+            let result = self.process_inbox().await;
+            self.send_error_if_failed(result);
         }
     }
 
@@ -118,6 +153,10 @@ impl DataStorage {
             backend_send,
             gui_recv,
             exiting: false,
+            ingest_settings: IngestSettings::default(),
+            inbox_settings: InboxSettings::default(),
+            inbox_watcher: None,
+            inbox_target: None,
             image_cache: ImageCache {
                 images_cache: HashMap::with_capacity(100),
                 thumbnails_cache: HashMap::with_capacity(100),
@@ -156,15 +195,150 @@ impl DataStorage {
                         self.save_manga_entry(entry).await;
                     }
                 }
-                GuiCommand::AddImagesFromDisk(entry) => self.add_images_from_disk(entry).await,
+                GuiCommand::AddImagesFromDisk(entry) => {
+                    let result = self.add_images_from_disk(entry).await;
+                    self.send_error_if_failed(result);
+                }
                 GuiCommand::AddImageFromClipboard(entry) => {
-                    self.add_image_from_clipboard(entry).await;
+                    let result = self.add_image_from_clipboard(entry).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::AddImagesFromPaths(entry, paths) => {
+                    let result = self.add_images_from_paths(entry, paths).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::OpenArchive(entry) => {
+                    let result = self.open_archive(entry);
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::AddImagesFromArchive(entry, path, names) => {
+                    let result = self.add_images_from_archive(entry, &path, &names).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::OpenImageEditor(image) => {
+                    let result = self.open_image_editor(image);
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::LoadFullImage(image) => {
+                    let result = self.load_full_image(&image);
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::SaveEditedImage(image, pixels, replace) => {
+                    let result = self.save_edited_image(image, pixels, replace).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::SaveIngestSettings(settings) => {
+                    self.save_setting(crate::image_ingest::SETTING_NAME, &settings)
+                        .await;
+                    self.ingest_settings = settings;
+                }
+                GuiCommand::SaveInboxSettings(settings) => {
+                    self.save_setting(crate::inbox::SETTING_NAME, &settings)
+                        .await;
+                    self.inbox_settings = settings;
+                    let result = self.start_inbox();
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::SetInboxTarget(entry) => self.inbox_target = entry,
+                GuiCommand::AddInboxFiles(entry, paths) => {
+                    let result = self.add_inbox_files(entry, paths).await;
+                    self.send_error_if_failed(result);
                 }
                 GuiCommand::UpdateEntryImages(entry) => {
                     self.send_manga_entry_images(entry.id).await;
                 }
-                GuiCommand::ExportGroup(group) => self.export_group(group).await,
-                GuiCommand::AddNamesFromFolder(group) => self.add_names_from_folder(group).await,
+                GuiCommand::ExportGroup(group, template, image_options) => {
+                    let result = self.export_group(group, &template, image_options).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::GetExportTemplates => self.send_export_templates(),
+                GuiCommand::ExportGroupText(group, format) => {
+                    let result = self.export_group_text(group, format).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::ExportGroupMarkdown(group, front_matter, image_options) => {
+                    let result = self
+                        .export_group_markdown(group, front_matter, image_options)
+                        .await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::ExportGroupPdf(group) => {
+                    let result = self.export_group_pdf(group).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::ExportEpub(groups) => {
+                    let result = self.export_epub(groups).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::ExportGroupImage(group, options) => {
+                    let result = self.export_group_image(group, options).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::ExportSite(layout, template, image_options) => {
+                    let result = self.export_site(layout, &template, image_options).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::PreviewExport(group, template, image_options) => {
+                    let result = self.preview_export(group, &template, image_options).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::RepeatExport(export) => {
+                    let result = self.repeat_export(export).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::LintGroup(group, entries, action) => {
+                    let result = self.lint_group(group, &entries, action).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::AddNamesFromFolder(group, rules, remember_folder) => {
+                    let result = self
+                        .add_names_from_folder(group, rules, remember_folder)
+                        .await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::SyncGroupFolder(group, rules) => {
+                    let result = self.sync_group_folder(group, &rules).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::ApplyFolderNameReview(review) => {
+                    self.apply_folder_name_review(review).await;
+                }
+                GuiCommand::ApplyFolderSync(plan) => {
+                    let result = self.apply_folder_sync(plan).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::ImportFromSources(group, options) => {
+                    let result = self.import_from_sources(group, options).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::RepickSourcePages(entry, options) => {
+                    let result = self.repick_source_pages(entry, options).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::ExportEntriesCsv(group) => {
+                    let result = self.export_entries_csv(group).await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::PreviewCsvImport => {
+                    let result = self.preview_csv_import().await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::ApplyCsvImport(changes) => self.apply_csv_import(changes).await,
+                GuiCommand::PreviewListImport => {
+                    let result = self.preview_list_import();
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::PreviewMihonImport => {
+                    let result = self.preview_mihon_import();
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::ImportHtmlExports => {
+                    let result = self.import_html_exports().await;
+                    self.send_error_if_failed(result);
+                }
+                GuiCommand::ImportEntries(group, entries) => {
+                    self.import_entries(group, entries).await;
+                }
             }
         }
     }
@@ -175,6 +349,45 @@ impl DataStorage {
             .unwrap();
     }
 
+    fn send_export_templates(&self) {
+        self.backend_send
+            .send(BackendCommand::UpdateExportTemplates(
+                crate::manga_group_export::list_templates(&self.cwd),
+            ))
+            .unwrap();
+    }
+
+    /// Setting stored as JSON, or its default when it's missing or can't be read.
+    async fn load_setting<T: serde::de::DeserializeOwned + Default>(&self, name: &str) -> T {
+        sqlx::query!(r"SELECT value FROM settings WHERE name = ?", name)
+            .fetch_optional(&self.db_pool)
+            .await
+            .unwrap()
+            .and_then(|x| serde_json::from_str(&x.value).ok())
+            .unwrap_or_default()
+    }
+
+    async fn save_setting<T: serde::Serialize>(&self, name: &str, value: &T) {
+        let value = serde_json::to_string(value).unwrap();
+        sqlx::query!(
+            r"INSERT INTO settings(name, value) VALUES(?, ?)
+            ON CONFLICT(name) DO UPDATE SET value = excluded.value",
+            name,
+            value
+        )
+        .execute(&self.db_pool)
+        .await
+        .unwrap();
+    }
+
+    fn send_error_if_failed(&self, result: AnyResult<()>) {
+        if let Err(error) = result {
+            self.backend_send
+                .send(BackendCommand::ShowError(format!("{error:#}")))
+                .unwrap();
+        }
+    }
+
     async fn create_new_manga_entry(&mut self, group: MangaGroup) {
         sqlx::query!(
             r"INSERT INTO manga_entries(manga_group) VALUES(?)",
@@ -187,17 +400,19 @@ impl DataStorage {
         self.send_selected_group(group).await;
     }
 
-    async fn create_new_manga_entry_with_name(&mut self, group: &MangaGroup, name: &str) {
+    async fn insert_manga_entry(&self, entry: &MangaEntry) -> i64 {
         sqlx::query!(
-            r"INSERT INTO manga_entries(manga_group, name) VALUES(?, ?)",
-            group.id,
-            name
+            r"INSERT INTO manga_entries(manga_group, name, score, comment, source_path) VALUES(?, ?, ?, ?, ?)",
+            entry.manga_group,
+            entry.name,
+            entry.score,
+            entry.comment,
+            entry.source_path
         )
         .execute(&self.db_pool)
         .await
-        .unwrap();
-
-        self.send_selected_group(group.clone()).await;
+        .unwrap()
+        .last_insert_rowid()
     }
 
     async fn create_new_manga_group(&mut self) {
@@ -253,6 +468,7 @@ impl DataStorage {
         self.backend_send
             .send(BackendCommand::UpdateSelectedGroup(result))
             .unwrap();
+        self.send_export_history(&group).await;
     }
 
     async fn save_manga_entry(&self, entry: MangaEntry) {
@@ -276,7 +492,15 @@ impl DataStorage {
             .unwrap();
     }
 
-    async fn add_image_shared(&mut self, entry: &MangaEntry, image_file: image::DynamicImage) {
+    /// Stores the image according to the ingest settings.
+    async fn add_image_shared(
+        &mut self,
+        entry: &MangaEntry,
+        image: IngestImage,
+        from_source: bool,
+    ) -> AnyResult<()> {
+        let (contents, extension) = self.ingest_settings.prepare(image)?;
+
         // TODO: find a way to avoid making this query just to get group id
         let manga_group = sqlx::query!(
             r"SELECT manga_group FROM manga_entries WHERE manga_entries.id = ? LIMIT 1",
@@ -294,69 +518,337 @@ impl DataStorage {
                 std::fs::create_dir_all(full_folder_path).unwrap();
             }
 
-            format!("{}/{}.jpg", relative_folder_path, uuid::Uuid::new_v4())
+            format!(
+                "{}/{}.{extension}",
+                relative_folder_path,
+                uuid::Uuid::new_v4()
+            )
         };
         let full_image_path = self.cwd.join(&relative_image_path);
-
-        let new_file =
-            &mut std::io::BufWriter::new(std::fs::File::create(&full_image_path).unwrap());
-        let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(new_file, 95);
-
-        encoder
-            .encode(
-                &image_file.to_rgb8(),
-                image_file.width(),
-                image_file.height(),
-                image::ExtendedColorType::Rgb8,
-            )
-            .unwrap();
+        std::fs::write(&full_image_path, contents)
+            .with_context(|| format!("Failed to create '{}'.", full_image_path.display()))?;
 
         sqlx::query!(
-            r"INSERT INTO manga_images(path, manga) VALUES(?, ?)",
+            r"INSERT INTO manga_images(path, manga, from_source) VALUES(?, ?, ?)",
             relative_image_path,
             entry.id,
+            from_source,
         )
         .execute(&self.db_pool)
         .await
         .unwrap();
+        Ok(())
+    }
+
+    async fn add_images_shared(
+        &mut self,
+        entry: &MangaEntry,
+        images: Vec<IngestImage>,
+        from_source: bool,
+    ) -> AnyResult<()> {
+        for image in images {
+            self.add_image_shared(entry, image, from_source).await?;
+        }
+        Ok(())
     }
 
-    async fn add_images_from_disk(&mut self, entry: MangaEntry) {
+    /// Adds pages of an archive or chapter folder, reporting the ones that failed together.
+    async fn add_pages(
+        &mut self,
+        entry: &MangaEntry,
+        pages: Vec<(String, IngestImage)>,
+        from_source: bool,
+    ) -> AnyResult<()> {
+        let mut failed = Vec::new();
+        for (name, image) in pages {
+            if let Err(error) = self.add_image_shared(entry, image, from_source).await {
+                failed.push(format!("{name}: {error:#}"));
+            }
+        }
+        if !failed.is_empty() {
+            anyhow::bail!("Skipped pages that can't be added:\n{}", failed.join("\n"));
+        }
+        Ok(())
+    }
+
+    async fn add_images_from_disk(&mut self, entry: MangaEntry) -> AnyResult<()> {
         let images_file_path = rfd::FileDialog::new()
             .set_title("Select image")
             .set_directory(&self.cwd)
-            .add_filter("Images", &["jpg", "jpeg", "png"])
+            .add_filter("Images", &crate::image_ingest::readable_extensions())
             .pick_files();
 
-        if images_file_path.is_none() {
-            return;
+        let Some(images_file_path) = images_file_path else {
+            return Ok(());
+        };
+        self.add_image_files(&entry, images_file_path).await
+    }
+
+    /// Adds every file, reporting the ones that failed together.
+    async fn add_image_files(&mut self, entry: &MangaEntry, paths: Vec<PathBuf>) -> AnyResult<()> {
+        let mut failed = Vec::new();
+        for path in paths {
+            let result = match std::fs::read(&path) {
+                Ok(contents) => {
+                    self.add_image_shared(entry, IngestImage::file(contents, &path), false)
+                        .await
+                }
+                Err(error) => Err(error.into()),
+            };
+            if let Err(error) = result {
+                failed.push(format!("{}: {error:#}", path.display()));
+            }
+        }
+        if !failed.is_empty() {
+            anyhow::bail!("Failed to add some images:\n{}", failed.join("\n"));
+        }
+        Ok(())
+    }
+
+    /// Adds dropped files and all supported images inside dropped folders.
+    async fn add_images_from_paths(
+        &mut self,
+        entry: MangaEntry,
+        paths: Vec<PathBuf>,
+    ) -> AnyResult<()> {
+        let mut image_paths = Vec::new();
+        for path in paths {
+            if path.is_dir() {
+                let mut folder_images = std::fs::read_dir(&path)
+                    .with_context(|| format!("Failed to read folder '{}'.", path.display()))?
+                    .filter_map(Result::ok)
+                    .map(|x| x.path())
+                    .filter(|x| x.is_file() && is_supported_image(x))
+                    .collect::<Vec<_>>();
+                folder_images.sort_by(|a, b| {
+                    natord::compare(
+                        &a.file_name().unwrap().to_string_lossy(),
+                        &b.file_name().unwrap().to_string_lossy(),
+                    )
+                });
+                image_paths.extend(folder_images);
+            } else if is_supported_image(&path) {
+                image_paths.push(path);
+            }
         }
+        if image_paths.is_empty() {
+            anyhow::bail!("No supported images among the dropped files.");
+        }
+
+        self.add_image_files(&entry, image_paths).await
+    }
 
-        for image_file_path in images_file_path.unwrap() {
-            let file_contents = std::fs::read(image_file_path).unwrap();
-            let loaded_image = image::load_from_memory(&file_contents).unwrap();
+    /// Watches the inbox folder and fills the tray with the files already in it.
+    fn start_inbox(&mut self) -> AnyResult<()> {
+        self.inbox_watcher = None;
+        self.backend_send
+            .send(BackendCommand::UpdateInboxSettings(
+                self.inbox_settings.clone(),
+            ))
+            .unwrap();
+        self.backend_send
+            .send(BackendCommand::ShowInboxItems(Vec::new()))
+            .unwrap();
 
-            self.add_image_shared(&entry, loaded_image).await;
+        let Some(folder) = self.inbox_settings.folder.clone() else {
+            return Ok(());
+        };
+        self.inbox_watcher = Some(InboxWatcher::new(&folder)?);
+        let items = crate::inbox::load_items(crate::inbox::existing_files(&folder)?);
+        self.backend_send
+            .send(BackendCommand::ShowInboxItems(items))
+            .unwrap();
+        Ok(())
+    }
+
+    /// Picks up new inbox files, adding them to the target entry or to the tray.
+    async fn process_inbox(&mut self) -> AnyResult<()> {
+        let Some(watcher) = &mut self.inbox_watcher else {
+            return Ok(());
+        };
+        let files = watcher.settled_files();
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        // Target entry could've been deleted since it was chosen
+        if let Some(entry) = &self.inbox_target {
+            let exists = sqlx::query!(r"SELECT id FROM manga_entries WHERE id = ?", entry.id)
+                .fetch_optional(&self.db_pool)
+                .await
+                .unwrap()
+                .is_some();
+            if !exists {
+                self.inbox_target = None;
+            }
+        }
+
+        match self.inbox_target.clone() {
+            Some(entry) => self.add_inbox_files(entry, files).await,
+            None => {
+                self.backend_send
+                    .send(BackendCommand::AddInboxItems(crate::inbox::load_items(
+                        files,
+                    )))
+                    .unwrap();
+                Ok(())
+            }
         }
     }
 
-    async fn add_image_from_clipboard(&mut self, entry: MangaEntry) {
-        let mut buffer = Vec::with_capacity(500_000);
-        {
-            use clipboard_win::Getter;
-            let _clip = clipboard_win::Clipboard::new_attempts(10).expect("Open clipboard");
-            let read_bytes = clipboard_win::formats::Bitmap
-                .read_clipboard(&mut buffer)
-                .unwrap();
-            buffer.truncate(read_bytes);
+    /// Adds inbox files to the entry, then moves or deletes them.
+    async fn add_inbox_files(&mut self, entry: MangaEntry, paths: Vec<PathBuf>) -> AnyResult<()> {
+        let mut imported = Vec::new();
+        let mut failed = Vec::new();
+        for path in paths {
+            let result = match std::fs::read(&path) {
+                Ok(contents) => {
+                    self.add_image_shared(&entry, IngestImage::file(contents, &path), false)
+                        .await
+                }
+                Err(error) => Err(error.into()),
+            };
+            match result {
+                Ok(()) => {
+                    let result =
+                        crate::inbox::finish_import(&path, self.inbox_settings.after_import);
+                    if let Err(error) = result {
+                        failed.push(format!("{error:#}"));
+                    }
+                    imported.push(path);
+                }
+                Err(error) => failed.push(format!("{}: {error:#}", path.display())),
+            }
         }
 
-        let image = image::ImageReader::new(std::io::Cursor::new(&buffer))
-            .with_guessed_format()
-            .unwrap()
-            .decode()
+        self.backend_send
+            .send(BackendCommand::RemoveInboxItems(imported))
             .unwrap();
-        self.add_image_shared(&entry, image).await;
+        self.send_manga_entry_images(entry.id).await;
+        if !failed.is_empty() {
+            anyhow::bail!("Failed to import some inbox files:\n{}", failed.join("\n"));
+        }
+        Ok(())
+    }
+
+    fn open_archive(&self, entry: MangaEntry) -> AnyResult<()> {
+        let archive_path = rfd::FileDialog::new()
+            .set_title("Select chapter archive")
+            .set_directory(&self.cwd)
+            .add_filter(
+                "Chapter archives",
+                &crate::archive_import::ARCHIVE_EXTENSIONS,
+            )
+            .pick_file();
+
+        let Some(archive_path) = archive_path else {
+            return Ok(());
+        };
+        let pages = crate::archive_import::load_pages(entry, archive_path)?;
+        self.backend_send
+            .send(BackendCommand::ShowArchivePages(pages))
+            .unwrap();
+        Ok(())
+    }
+
+    async fn add_images_from_archive(
+        &mut self,
+        entry: MangaEntry,
+        path: &std::path::Path,
+        names: &[String],
+    ) -> AnyResult<()> {
+        let pages = crate::archive_import::read_pages(path, names)?;
+        self.add_pages(&entry, pages, false).await
+    }
+
+    fn open_image_editor(&self, image: MangaImage) -> AnyResult<()> {
+        let full_image_path = self.cwd.join(&image.path);
+        let pixels = image::open(&full_image_path)
+            .with_context(|| format!("Failed to open '{}'.", full_image_path.display()))?
+            .to_rgba8();
+
+        self.backend_send
+            .send(BackendCommand::ShowImageEditor(image, pixels))
+            .unwrap();
+        Ok(())
+    }
+
+    fn load_full_image(&self, image: &MangaImage) -> AnyResult<()> {
+        let full_image_path = self.cwd.join(&image.path);
+        let mut full_image = image::open(&full_image_path)
+            .with_context(|| format!("Failed to open '{}'.", full_image_path.display()))?;
+        let max_side = crate::image_viewer::MAX_VIEWER_SIDE;
+        if full_image.width().max(full_image.height()) > max_side {
+            full_image =
+                full_image.resize(max_side, max_side, image::imageops::FilterType::Lanczos3);
+        }
+
+        let pixels = full_image.to_rgba8();
+        let color_image = egui::ColorImage::from_rgba_unmultiplied(
+            [
+                usize::try_from(pixels.width())?,
+                usize::try_from(pixels.height())?,
+            ],
+            pixels.as_raw(),
+        );
+        self.backend_send
+            .send(BackendCommand::ShowFullImage(image.id, color_image))
+            .unwrap();
+        Ok(())
+    }
+
+    async fn save_edited_image(
+        &mut self,
+        image: MangaImage,
+        pixels: image::RgbaImage,
+        replace: bool,
+    ) -> AnyResult<()> {
+        let image_file = IngestImage::Decoded(image::DynamicImage::ImageRgba8(pixels));
+        if replace {
+            // Ingest settings may change the format, and the extension along with it
+            let (contents, extension) = self.ingest_settings.prepare(image_file)?;
+            let new_path = std::path::Path::new(&image.path)
+                .with_extension(extension)
+                .to_string_lossy()
+                .to_string();
+            crate::export_common::write_file_atomically(&self.cwd.join(&new_path), &contents)?;
+            if new_path != image.path {
+                sqlx::query!(
+                    r"UPDATE manga_images SET path = ? WHERE id = ?",
+                    new_path,
+                    image.id
+                )
+                .execute(&self.db_pool)
+                .await
+                .unwrap();
+                std::fs::remove_file(self.cwd.join(&image.path))
+                    .with_context(|| format!("Failed to remove '{}'.", image.path))?;
+            }
+            self.image_cache.remove_image(&image);
+        } else {
+            let entry = sqlx::query_as!(
+                MangaEntry,
+                r"SELECT * FROM manga_entries WHERE id = ?",
+                image.manga
+            )
+            .fetch_one(&self.db_pool)
+            .await
+            .unwrap();
+            self.add_image_shared(&entry, image_file, false).await?;
+        }
+
+        self.send_manga_entry_images(image.manga).await;
+        Ok(())
+    }
+
+    async fn add_image_from_clipboard(&mut self, entry: MangaEntry) -> AnyResult<()> {
+        let images = {
+            let mut clipboard = crate::clipboard::open_image_clipboard()?;
+            crate::clipboard::read_images(clipboard.as_mut())?
+        };
+
+        let images = images.into_iter().map(IngestImage::from).collect();
+        self.add_images_shared(&entry, images, false).await
     }
 
     async fn send_manga_entry_images(&mut self, entry_id: i64) {
@@ -381,7 +873,10 @@ impl DataStorage {
             .unwrap();
     }
 
-    async fn export_group(&self, group: MangaGroup) {
+    async fn get_group_entries_with_images(
+        &self,
+        group: &MangaGroup,
+    ) -> Vec<(MangaEntry, Vec<MangaImage>)> {
         let group_entries = sqlx::query_as!(
             MangaEntry,
             r"SELECT * FROM manga_entries WHERE manga_group = ? ORDER BY id DESC",
@@ -405,45 +900,654 @@ impl DataStorage {
             entries.push((entry, manga_images));
         }
 
-        crate::manga_group_export::MangaGroupExporter::new(group, entries).export_group();
+        entries
     }
 
-    async fn add_names_from_folder(&mut self, group: MangaGroup) {
-        let folder_name = {
-            let folder_name = rfd::FileDialog::new()
-                .set_title("Select folder to load entries from")
-                .set_directory(std::env::current_dir().unwrap())
-                .pick_folder();
+    async fn export_group(
+        &self,
+        group: MangaGroup,
+        template: &str,
+        image_options: ExportImageOptions,
+    ) -> AnyResult<()> {
+        let entries = self.get_group_entries_with_images(&group).await;
+        let export_path = crate::manga_group_export::MangaGroupExporter::new(
+            group.clone(),
+            entries,
+            template,
+            image_options,
+        )?
+        .export_group()?;
 
-            if folder_name.is_none() {
-                return;
+        if let Some(export_path) = export_path {
+            self.record_export(
+                &[group],
+                &export_path.to_string_lossy(),
+                template,
+                &ExportKind::Template(image_options),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Adds the export to the history of every group in it. `template` is the template
+    /// name for template exports, and a label of the export kind for the others.
+    async fn record_export(
+        &self,
+        groups: &[MangaGroup],
+        path: &str,
+        template: &str,
+        kind: &ExportKind,
+    ) -> AnyResult<()> {
+        let options = serde_json::to_string(kind)?;
+        for group in groups {
+            sqlx::query!(
+                r"INSERT INTO exports (path, template, options, manga_group) VALUES (?, ?, ?, ?)",
+                path,
+                template,
+                options,
+                group.id
+            )
+            .execute(&self.db_pool)
+            .await?;
+
+            self.send_export_history(group).await;
+        }
+        Ok(())
+    }
+
+    async fn send_export_history(&self, group: &MangaGroup) {
+        let exports = sqlx::query_as!(
+            MangaExport,
+            r"SELECT * FROM exports WHERE manga_group = ? ORDER BY exported_on DESC, id DESC",
+            group.id
+        )
+        .fetch_all(&self.db_pool)
+        .await
+        .unwrap();
+
+        self.backend_send
+            .send(BackendCommand::UpdateExportHistory(group.id, exports))
+            .unwrap();
+    }
+
+    /// Exports the group again into the same file, with the template and options used back then.
+    async fn repeat_export(&mut self, export: MangaExport) -> AnyResult<()> {
+        let group = sqlx::query_as!(
+            MangaGroup,
+            r"SELECT * FROM manga_groups WHERE id = ?",
+            export.manga_group
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+        let kind = ExportKind::parse(&export.options)?;
+        let path = std::path::Path::new(&export.path);
+
+        let groups = match &kind {
+            ExportKind::Template(image_options) => {
+                let entries = self.get_group_entries_with_images(&group).await;
+                crate::manga_group_export::MangaGroupExporter::new(
+                    group.clone(),
+                    entries,
+                    &export.template,
+                    *image_options,
+                )?
+                .export_to(path)?;
+                vec![group]
+            }
+            ExportKind::Markdown {
+                front_matter,
+                image_options,
+            } => {
+                let entries = self.get_group_entries_with_images(&group).await;
+                crate::markdown_export::MarkdownExporter::new(
+                    group.clone(),
+                    entries,
+                    *front_matter,
+                    *image_options,
+                )?
+                .export_to(path)?;
+                vec![group]
+            }
+            ExportKind::Pdf => {
+                let entries = self.get_group_entries_with_images(&group).await;
+                crate::pdf_export::PdfExporter::new(group.clone(), entries)?.export_to(path)?;
+                vec![group]
+            }
+            ExportKind::Epub { groups: group_ids } => {
+                let mut groups = Vec::with_capacity(group_ids.len());
+                for group_id in group_ids {
+                    // Groups deleted since then are left out
+                    if let Some(group) = sqlx::query_as!(
+                        MangaGroup,
+                        r"SELECT * FROM manga_groups WHERE id = ?",
+                        group_id
+                    )
+                    .fetch_optional(&self.db_pool)
+                    .await?
+                    {
+                        groups.push(group);
+                    }
+                }
+                self.epub_exporter(&groups).await?.write_book(path)?;
+                groups
+            }
+            ExportKind::Image(options) => {
+                let entries = self.get_group_entries_with_images(&group).await;
+                crate::image_export::ImageExporter::new(group.clone(), entries, options.clone())?
+                    .export_to(path)?;
+                vec![group]
+            }
+            ExportKind::Site {
+                layout,
+                image_options,
+            } => {
+                self.site_exporter(*layout, &export.template, *image_options)
+                    .await?
+                    .export_to(path)?;
+                self.manga_groups.clone()
             }
+            ExportKind::Text { format } => {
+                self.export_group_text(group, *format).await?;
+                return Ok(());
+            }
+        };
+
+        self.record_export(&groups, &export.path, &export.template, &kind)
+            .await
+    }
+
+    async fn preview_export(
+        &self,
+        group: MangaGroup,
+        template: &str,
+        image_options: ExportImageOptions,
+    ) -> AnyResult<()> {
+        let entries = self.get_group_entries_with_images(&group).await;
+        let preview = crate::manga_group_export::MangaGroupExporter::new(
+            group,
+            entries,
+            template,
+            image_options,
+        )?
+        .preview()?;
+
+        self.backend_send
+            .send(BackendCommand::ShowExportPreview(preview))
+            .unwrap();
+        Ok(())
+    }
+
+    async fn lint_group(
+        &self,
+        group: MangaGroup,
+        entries: &[MangaEntry],
+        action: LintAction,
+    ) -> AnyResult<()> {
+        let cwd = std::env::current_dir().context("Unable to get CWD.")?;
+        let stored_entries = self.get_group_entries_with_images(&group).await;
+        let problems = crate::export_lint::lint_group(entries, &stored_entries, &cwd);
+
+        self.backend_send
+            .send(BackendCommand::ShowLintReport(
+                crate::export_lint::LintReport {
+                    group,
+                    action,
+                    problems,
+                },
+            ))
+            .unwrap();
+        Ok(())
+    }
+
+    async fn export_group_markdown(
+        &self,
+        group: MangaGroup,
+        front_matter: bool,
+        image_options: ExportImageOptions,
+    ) -> AnyResult<()> {
+        let entries = self.get_group_entries_with_images(&group).await;
+        let export_path = crate::markdown_export::MarkdownExporter::new(
+            group.clone(),
+            entries,
+            front_matter,
+            image_options,
+        )?
+        .export_group()?;
+
+        if let Some(export_path) = export_path {
+            let kind = ExportKind::Markdown {
+                front_matter,
+                image_options,
+            };
+            self.record_export(&[group], &export_path.to_string_lossy(), "Markdown", &kind)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn export_group_pdf(&self, group: MangaGroup) -> AnyResult<()> {
+        let entries = self.get_group_entries_with_images(&group).await;
+        let export_path =
+            crate::pdf_export::PdfExporter::new(group.clone(), entries)?.export_group()?;
+
+        if let Some(export_path) = export_path {
+            self.record_export(
+                &[group],
+                &export_path.to_string_lossy(),
+                "PDF",
+                &ExportKind::Pdf,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn epub_exporter(
+        &self,
+        groups: &[MangaGroup],
+    ) -> AnyResult<crate::epub_export::EpubExporter> {
+        let mut groups_with_entries = Vec::with_capacity(groups.len());
+        for group in groups {
+            let entries = self.get_group_entries_with_images(group).await;
+            groups_with_entries.push((group.clone(), entries));
+        }
+        crate::epub_export::EpubExporter::new(groups_with_entries)
+    }
+
+    async fn export_epub(&self, groups: Vec<MangaGroup>) -> AnyResult<()> {
+        let export_path = self.epub_exporter(&groups).await?.export_groups()?;
+
+        if let Some(export_path) = export_path {
+            let kind = ExportKind::Epub {
+                groups: groups.iter().map(|x| x.id).collect(),
+            };
+            self.record_export(&groups, &export_path.to_string_lossy(), "EPUB", &kind)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn export_group_image(
+        &self,
+        group: MangaGroup,
+        options: ImageExportOptions,
+    ) -> AnyResult<()> {
+        let entries = self.get_group_entries_with_images(&group).await;
+        let export_path =
+            crate::image_export::ImageExporter::new(group.clone(), entries, options.clone())?
+                .export_group()?;
+
+        if let Some(export_path) = export_path {
+            self.record_export(
+                &[group],
+                &export_path.to_string_lossy(),
+                "Image",
+                &ExportKind::Image(options),
+            )
+            .await?;
+        }
+        Ok(())
+    }
 
-            folder_name.unwrap()
+    /// Exporter of every group, with the list of groups refreshed first.
+    async fn site_exporter(
+        &mut self,
+        layout: SiteLayout,
+        template: &str,
+        image_options: ExportImageOptions,
+    ) -> AnyResult<crate::site_export::SiteExporter<'static>> {
+        self.update_manga_groups().await;
+
+        let mut groups = Vec::with_capacity(self.manga_groups.len());
+        for group in &self.manga_groups {
+            let entries = self.get_group_entries_with_images(group).await;
+            groups.push((group.clone(), entries));
+        }
+        crate::site_export::SiteExporter::new(groups, layout, template, image_options)
+    }
+
+    async fn export_site(
+        &mut self,
+        layout: SiteLayout,
+        template: &str,
+        image_options: ExportImageOptions,
+    ) -> AnyResult<()> {
+        let site_folder = self
+            .site_exporter(layout, template, image_options)
+            .await?
+            .export_site()?;
+
+        if let Some(site_folder) = site_folder {
+            let kind = ExportKind::Site {
+                layout,
+                image_options,
+            };
+            self.record_export(
+                &self.manga_groups,
+                &site_folder.to_string_lossy(),
+                template,
+                &kind,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn export_group_text(
+        &self,
+        group: MangaGroup,
+        format: TextExportFormat,
+    ) -> AnyResult<()> {
+        let entries = self.get_group_entries_with_images(&group).await;
+        let messages =
+            crate::text_export::TextExporter::new(group.clone(), entries, format).render();
+
+        self.backend_send
+            .send(BackendCommand::ShowTextExport((format, messages)))
+            .unwrap();
+        self.record_export(
+            &[group],
+            "",
+            &format!("Text ({})", format.name()),
+            &ExportKind::Text { format },
+        )
+        .await
+    }
+
+    async fn export_entries_csv(&mut self, group: Option<MangaGroup>) -> AnyResult<()> {
+        let file_name = match &group {
+            Some(group) => format!("entries_group_{}.csv", group.id),
+            None => "entries_library.csv".to_string(),
+        };
+        let export_path = rfd::FileDialog::new()
+            .set_title("Select CSV export destination")
+            .set_directory(&self.cwd)
+            .add_filter("CSV file", &["csv"])
+            .set_file_name(file_name)
+            .save_file();
+
+        let Some(export_path) = export_path else {
+            return Ok(());
         };
 
-        let folder_entries = {
-            let mut set = std::collections::HashSet::with_capacity(100);
-            let contents = std::fs::read_dir(folder_name);
-            if contents.is_err() {
-                return;
+        let groups = match group {
+            Some(group) => vec![group],
+            None => {
+                self.update_manga_groups().await;
+                self.manga_groups.iter().rev().cloned().collect()
             }
-            for entry in contents.unwrap() {
-                if entry.is_err() {
-                    continue;
+        };
+        let mut entries = Vec::with_capacity(groups.len() * 10);
+        for group in &groups {
+            let mut group_entries = self.get_group_entries_with_images(group).await;
+            group_entries.reverse();
+            entries.append(&mut group_entries);
+        }
+
+        crate::csv_transfer::write_entries(&export_path, &entries)
+    }
+
+    async fn preview_csv_import(&mut self) -> AnyResult<()> {
+        let import_path = rfd::FileDialog::new()
+            .set_title("Select CSV file to import")
+            .set_directory(&self.cwd)
+            .add_filter("CSV file", &["csv"])
+            .pick_file();
+
+        let Some(import_path) = import_path else {
+            return Ok(());
+        };
+
+        let existing_entries = sqlx::query_as!(MangaEntry, r"SELECT * FROM manga_entries")
+            .fetch_all(&self.db_pool)
+            .await
+            .unwrap();
+        self.update_manga_groups().await;
+        let existing_groups = self.manga_groups.iter().map(|x| x.id).collect();
+
+        let changes =
+            crate::csv_transfer::plan_import(&import_path, &existing_entries, &existing_groups)?;
+        self.backend_send
+            .send(BackendCommand::ShowCsvImportPreview(changes))
+            .unwrap();
+        Ok(())
+    }
+
+    async fn apply_csv_import(&mut self, changes: Vec<CsvImportChange>) {
+        for change in changes {
+            match change {
+                CsvImportChange::Update { after, .. } => self.save_manga_entry(after).await,
+                CsvImportChange::Create(entry) => {
+                    self.insert_manga_entry(&entry).await;
                 }
-                let entry = entry.unwrap();
-                if !entry.path().is_dir() {
-                    continue;
+                CsvImportChange::Skip { .. } => {}
+            }
+        }
+    }
+
+    fn preview_list_import(&self) -> AnyResult<()> {
+        let import_path = rfd::FileDialog::new()
+            .set_title("Select MyAnimeList or AniList export file")
+            .set_directory(&self.cwd)
+            .add_filter("List export", &["xml", "gz", "json"])
+            .pick_file();
+
+        let Some(import_path) = import_path else {
+            return Ok(());
+        };
+
+        let list_import = crate::list_import::read_list_file(&import_path)?;
+        self.backend_send
+            .send(BackendCommand::ShowListImportPreview(list_import))
+            .unwrap();
+        Ok(())
+    }
+
+    fn preview_mihon_import(&self) -> AnyResult<()> {
+        let import_path = rfd::FileDialog::new()
+            .set_title("Select Mihon/Tachiyomi backup file")
+            .set_directory(&self.cwd)
+            .add_filter("Mihon backup", &["tachibk", "gz"])
+            .pick_file();
+
+        let Some(import_path) = import_path else {
+            return Ok(());
+        };
+
+        let list_import = crate::mihon_import::read_backup_file(&import_path)?;
+        self.backend_send
+            .send(BackendCommand::ShowListImportPreview(list_import))
+            .unwrap();
+        Ok(())
+    }
+
+    /// Rebuilds groups, entries and images from files made by `MangaGroupExporter`.
+    async fn import_html_exports(&mut self) -> AnyResult<()> {
+        let import_paths = rfd::FileDialog::new()
+            .set_title("Select exported HTML files")
+            .set_directory(&self.cwd)
+            .add_filter("HTML file", &["html"])
+            .pick_files();
+
+        let Some(import_paths) = import_paths else {
+            return Ok(());
+        };
+
+        let mut reviews = Vec::with_capacity(import_paths.len());
+        for import_path in &import_paths {
+            reviews.push(crate::html_import::read_export_file(import_path)?);
+        }
+
+        let mut missing_images = Vec::new();
+        let mut already_imported = Vec::new();
+        for (review, import_path) in reviews.into_iter().zip(&import_paths) {
+            if let Some(group_id) = self.find_imported_group(&review).await {
+                already_imported.push(format!(
+                    "'{}' is group #{group_id:03}",
+                    import_path.display()
+                ));
+                continue;
+            }
+
+            let group_id = sqlx::query!(
+                r"INSERT INTO manga_groups(added_on) VALUES(?)",
+                review.added_on
+            )
+            .execute(&self.db_pool)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+
+            for imported_entry in review.entries {
+                let mut entry = MangaEntry {
+                    name: imported_entry.name,
+                    score: imported_entry.score,
+                    comment: imported_entry.comment,
+                    manga_group: group_id,
+                    id: 0,
+                    source_path: None,
+                    missing_from_source: false,
+                };
+                entry.id = self.insert_manga_entry(&entry).await;
+
+                for image_path in imported_entry.images {
+                    let added = match std::fs::read(&image_path) {
+                        Ok(contents) => self
+                            .add_image_shared(
+                                &entry,
+                                IngestImage::file(contents, &image_path),
+                                false,
+                            )
+                            .await
+                            .is_ok(),
+                        Err(_) => false,
+                    };
+                    if !added {
+                        missing_images.push(image_path.display().to_string());
+                    }
                 }
-                let name = entry.file_name().to_string_lossy().to_string();
-                set.insert(name);
             }
-            set
+        }
+
+        self.update_manga_groups().await;
+        self.send_updated_manga_groups();
+
+        let mut problems = Vec::new();
+        if !already_imported.is_empty() {
+            problems.push(format!(
+                "These files were imported before and were skipped:\n{}",
+                already_imported.join("\n")
+            ));
+        }
+        if !missing_images.is_empty() {
+            problems.push(format!(
+                "Groups were imported, but these images couldn't be loaded:\n{}",
+                missing_images.join("\n")
+            ));
+        }
+        if !problems.is_empty() {
+            anyhow::bail!("{}", problems.join("\n\n"));
+        }
+        Ok(())
+    }
+
+    /// Group made by an earlier import of the same file: added on the same date,
+    /// with entries of the same names.
+    async fn find_imported_group(&self, review: &ImportedReview) -> Option<i64> {
+        let mut names: Vec<&str> = review.entries.iter().map(|x| x.name.as_str()).collect();
+        names.sort_unstable();
+
+        let groups = sqlx::query_as!(MangaGroup, r"SELECT * FROM manga_groups")
+            .fetch_all(&self.db_pool)
+            .await
+            .unwrap();
+        for group in groups.into_iter().filter(|x| x.added_on == review.added_on) {
+            let mut group_names = sqlx::query!(
+                r"SELECT name FROM manga_entries WHERE manga_group = ?",
+                group.id
+            )
+            .fetch_all(&self.db_pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|x| x.name)
+            .collect::<Vec<_>>();
+            group_names.sort_unstable();
+            if group_names == names {
+                return Some(group.id);
+            }
+        }
+        None
+    }
+
+    /// Creates entries in the group, or in a new group if none is given, then selects that group.
+    async fn import_entries(&mut self, group: Option<MangaGroup>, entries: Vec<MangaEntry>) {
+        let group = match group {
+            Some(group) => group,
+            None => {
+                let group_id = sqlx::query!(r"INSERT INTO manga_groups DEFAULT VALUES")
+                    .execute(&self.db_pool)
+                    .await
+                    .unwrap()
+                    .last_insert_rowid();
+                self.update_manga_groups().await;
+                self.send_updated_manga_groups();
+
+                sqlx::query_as!(
+                    MangaGroup,
+                    r"SELECT * FROM manga_groups WHERE id = ?",
+                    group_id
+                )
+                .fetch_one(&self.db_pool)
+                .await
+                .unwrap()
+            }
         };
 
-        if folder_entries.is_empty() {
-            return;
+        for entry in entries {
+            self.insert_manga_entry(&MangaEntry {
+                manga_group: group.id,
+                ..entry
+            })
+            .await;
+        }
+
+        self.backend_send
+            .send(BackendCommand::SelectGroup(group))
+            .unwrap();
+    }
+
+    async fn add_names_from_folder(
+        &mut self,
+        mut group: MangaGroup,
+        rules: NameCleanupRules,
+        remember_folder: bool,
+    ) -> AnyResult<()> {
+        let folder = rfd::FileDialog::new()
+            .set_title("Select folder to load entries from")
+            .set_directory(&self.cwd)
+            .pick_folder();
+
+        let Some(folder) = folder else {
+            return Ok(());
+        };
+        if remember_folder {
+            group.source_folder = Some(folder.to_string_lossy().to_string());
+            sqlx::query!(
+                r"UPDATE manga_groups SET source_folder = ? WHERE id = ?",
+                group.source_folder,
+                group.id
+            )
+            .execute(&self.db_pool)
+            .await
+            .unwrap();
+            self.update_manga_groups().await;
+            self.send_updated_manga_groups();
+        }
+        let folder_names = crate::folder_names::scan_folder(&folder, &rules)?;
+        if folder_names.is_empty() {
+            anyhow::bail!("No subfolders found in '{}'.", folder.display());
         }
 
         let group_entries = sqlx::query_as!(
@@ -456,7 +1560,7 @@ impl DataStorage {
         .unwrap();
 
         // Removing empty entries, so that they won't get in the way
-        let mut db_entries = std::collections::HashSet::with_capacity(group_entries.len());
+        let mut db_entries = Vec::with_capacity(group_entries.len());
         for entry in group_entries {
             if entry.name.trim().is_empty() && entry.comment.trim().is_empty() {
                 let manga_images = sqlx::query!(
@@ -472,14 +1576,287 @@ impl DataStorage {
                     continue;
                 }
             } else {
-                db_entries.insert(entry.name);
+                db_entries.push(entry);
             }
         }
-        for missing_name in folder_entries.difference(&db_entries) {
-            self.create_new_manga_entry_with_name(&group, missing_name)
+        self.send_selected_group(group.clone()).await;
+
+        let matches = crate::folder_names::match_names(folder_names, &db_entries, &rules);
+        self.backend_send
+            .send(BackendCommand::ShowFolderNameReview(FolderNameReview {
+                group,
+                folder,
+                matches,
+            }))
+            .unwrap();
+        Ok(())
+    }
+
+    /// Creates the checked entries and links the matched existing ones to their folders.
+    async fn apply_folder_name_review(&mut self, review: FolderNameReview) {
+        for name_match in review.matches {
+            let source_path = review.folder.join(&name_match.folder);
+            let source_path = source_path.to_string_lossy();
+            if name_match.create {
+                if name_match.name.trim().is_empty() {
+                    continue;
+                }
+                self.insert_manga_entry(&MangaEntry {
+                    name: name_match.name.trim().to_string(),
+                    score: 0,
+                    comment: String::new(),
+                    manga_group: review.group.id,
+                    id: 0,
+                    source_path: Some(source_path.to_string()),
+                    missing_from_source: false,
+                })
                 .await;
+            } else if let Some(existing) = name_match.existing {
+                // Entries already linked to another folder keep it
+                sqlx::query!(
+                    r"UPDATE manga_entries SET source_path = ? WHERE id = ? AND source_path IS NULL",
+                    source_path,
+                    existing.entry_id
+                )
+                .execute(&self.db_pool)
+                .await
+                .unwrap();
+            }
+        }
+
+        self.send_selected_group(review.group).await;
+    }
+
+    /// Creates an entry for each subfolder or archive, with a few of its pages attached.
+    async fn import_from_sources(
+        &mut self,
+        group: MangaGroup,
+        options: SourceImportOptions,
+    ) -> AnyResult<()> {
+        let folder = rfd::FileDialog::new()
+            .set_title("Select folder with chapters")
+            .set_directory(&self.cwd)
+            .pick_folder();
+
+        let Some(folder) = folder else {
+            return Ok(());
+        };
+        let sources = crate::source_import::find_sources(&folder)?;
+        if sources.is_empty() {
+            anyhow::bail!("No subfolders or archives found in '{}'.", folder.display());
+        }
+
+        let existing_names = sqlx::query!(
+            r"SELECT name FROM manga_entries WHERE manga_group = ?",
+            group.id
+        )
+        .fetch_all(&self.db_pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|x| x.name)
+        .collect::<std::collections::HashSet<_>>();
+
+        let mut failed = Vec::new();
+        for (name, source) in sources {
+            if existing_names.contains(&name) {
+                continue;
+            }
+
+            let mut entry = MangaEntry {
+                name,
+                score: 0,
+                comment: String::new(),
+                manga_group: group.id,
+                id: 0,
+                source_path: Some(source.to_string_lossy().to_string()),
+                missing_from_source: false,
+            };
+            entry.id = self.insert_manga_entry(&entry).await;
+
+            let result = match crate::source_import::load_sample_pages(&source, options) {
+                Ok(pages) => self.add_pages(&entry, pages, true).await,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                failed.push(format!("{}: {error:#}", entry.name));
+            }
         }
 
         self.send_selected_group(group).await;
+        if !failed.is_empty() {
+            anyhow::bail!(
+                "Failed to add pages of some entries:\n{}",
+                failed.join("\n")
+            );
+        }
+        Ok(())
+    }
+
+    /// Replaces pages taken from the source with a new sample, images added by hand are kept.
+    async fn repick_source_pages(
+        &mut self,
+        entry: MangaEntry,
+        options: SourceImportOptions,
+    ) -> AnyResult<()> {
+        let Some(source) = &entry.source_path else {
+            anyhow::bail!("Entry '{}' wasn't imported from a folder.", entry.name);
+        };
+        let pages = crate::source_import::load_sample_pages(std::path::Path::new(source), options)?;
+
+        let old_images = sqlx::query_as!(
+            MangaImage,
+            r"SELECT * FROM manga_images WHERE manga = ? AND from_source",
+            entry.id
+        )
+        .fetch_all(&self.db_pool)
+        .await
+        .unwrap();
+        for image in old_images {
+            self.image_cache.remove_image(&image);
+            image.delete_cascade(&self.db_pool).await;
+        }
+
+        self.add_pages(&entry, pages, true).await
+    }
+
+    async fn sync_group_folder(
+        &mut self,
+        group: MangaGroup,
+        rules: &NameCleanupRules,
+    ) -> AnyResult<()> {
+        let Some(folder) = group.source_folder.clone() else {
+            anyhow::bail!("Group #{} has no source folder.", group.id);
+        };
+        let folder = PathBuf::from(folder);
+        if !folder.is_dir() {
+            anyhow::bail!("Source folder '{}' doesn't exist.", folder.display());
+        }
+
+        let entries = sqlx::query_as!(
+            MangaEntry,
+            r"SELECT * FROM manga_entries WHERE manga_group = ? ORDER BY id DESC",
+            group.id
+        )
+        .fetch_all(&self.db_pool)
+        .await
+        .unwrap();
+        let plan = crate::folder_sync::plan_sync(group, &folder, entries, rules)?;
+
+        self.backend_send
+            .send(BackendCommand::ShowFolderSync(plan))
+            .unwrap();
+        Ok(())
+    }
+
+    async fn apply_folder_sync(&mut self, plan: FolderSyncPlan) -> AnyResult<()> {
+        // Files of deleted entries are only removed once the whole batch is saved
+        let mut deleted_images = Vec::new();
+        let mut transaction = self.db_pool.begin().await.unwrap();
+        for item in plan.items.into_iter().filter(|x| x.apply) {
+            match item.change {
+                SyncChange::Add { folder, name } => {
+                    let source_path = folder.to_string_lossy().to_string();
+                    sqlx::query!(
+                        r"INSERT INTO manga_entries(manga_group, name, score, comment, source_path) VALUES(?, ?, 0, '', ?)",
+                        plan.group.id,
+                        name,
+                        source_path
+                    )
+                    .execute(&mut *transaction)
+                    .await
+                    .unwrap();
+                }
+                SyncChange::Link { entry, folder, .. } => {
+                    let source_path = folder.to_string_lossy().to_string();
+                    sqlx::query!(
+                        r"UPDATE manga_entries SET source_path = ?, missing_from_source = FALSE WHERE id = ?",
+                        source_path,
+                        entry.id
+                    )
+                    .execute(&mut *transaction)
+                    .await
+                    .unwrap();
+                }
+                SyncChange::Rename {
+                    entry,
+                    folder,
+                    name,
+                    ..
+                } => {
+                    let source_path = folder.to_string_lossy().to_string();
+                    sqlx::query!(
+                        r"UPDATE manga_entries SET name = ?, source_path = ?, missing_from_source = FALSE WHERE id = ?",
+                        name,
+                        source_path,
+                        entry.id
+                    )
+                    .execute(&mut *transaction)
+                    .await
+                    .unwrap();
+                }
+                SyncChange::Vanished { entry, action } => match action {
+                    VanishedAction::Keep => {}
+                    VanishedAction::Flag => {
+                        sqlx::query!(
+                            r"UPDATE manga_entries SET missing_from_source = TRUE WHERE id = ?",
+                            entry.id
+                        )
+                        .execute(&mut *transaction)
+                        .await
+                        .unwrap();
+                    }
+                    VanishedAction::Delete => {
+                        deleted_images.extend(
+                            sqlx::query_as!(
+                                MangaImage,
+                                r"SELECT * FROM manga_images WHERE manga = ?",
+                                entry.id
+                            )
+                            .fetch_all(&mut *transaction)
+                            .await
+                            .unwrap(),
+                        );
+                        sqlx::query!(r"DELETE FROM manga_images WHERE manga = ?", entry.id)
+                            .execute(&mut *transaction)
+                            .await
+                            .unwrap();
+                        sqlx::query!(r"DELETE FROM manga_entries WHERE id = ?", entry.id)
+                            .execute(&mut *transaction)
+                            .await
+                            .unwrap();
+                    }
+                },
+            }
+        }
+        for entry in plan.restored {
+            sqlx::query!(
+                r"UPDATE manga_entries SET missing_from_source = FALSE WHERE id = ?",
+                entry.id
+            )
+            .execute(&mut *transaction)
+            .await
+            .unwrap();
+        }
+        transaction.commit().await.unwrap();
+
+        // Rows are gone already, so files that can't be removed are only reported
+        let mut failed_removals = Vec::new();
+        for image in deleted_images {
+            self.image_cache.remove_image(&image);
+            if let Err(error) = std::fs::remove_file(self.cwd.join(&image.path)) {
+                failed_removals.push(format!("'{}': {error}", image.path));
+            }
+        }
+
+        self.send_selected_group(plan.group).await;
+        if failed_removals.is_empty() {
+            Ok(())
+        } else {
+            anyhow::bail!(
+                "Failed to remove images of deleted entries:\n{}",
+                failed_removals.join("\n")
+            )
+        }
     }
 }
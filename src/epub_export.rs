@@ -0,0 +1,387 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+use rayon::prelude::*;
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write as _;
+
+use shared::types::{MangaEntry, MangaGroup, MangaImage};
+
+/// Large enough for e-reader screens while keeping the book small.
+const IMAGE_MAX_DIMENSION: u32 = 1600;
+const IMAGE_QUALITY: u8 = 85;
+
+static STYLESHEET: &str = "
+body { font-family: serif; margin: 0 0.5em; }
+h1, h2 { text-align: center; }
+p.score { text-align: center; font-weight: bold; }
+div.image { text-align: center; margin: 0.5em 0; page-break-inside: avoid; }
+div.image img { max-width: 100%; max-height: 95vh; }
+";
+
+type GroupEntries = Vec<(MangaEntry, Vec<MangaImage>)>;
+
+fn escape(text: &str) -> String {
+    quick_xml::escape::escape(text).into_owned()
+}
+
+/// Comment rendered from Markdown; raw HTML is escaped, since it may not be valid XHTML.
+fn comment_to_xhtml(comment: &str) -> String {
+    use pulldown_cmark::Event;
+
+    let parser =
+        pulldown_cmark::Parser::new_ext(comment, pulldown_cmark::Options::empty()).map(|event| {
+            match event {
+                Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+                event => event,
+            }
+        });
+    let mut result = String::with_capacity(comment.len() * 2);
+    pulldown_cmark::html::push_html(&mut result, parser);
+    result
+}
+
+fn xhtml_page(title: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="en" xml:lang="en">
+<head>
+<meta charset="UTF-8"/>
+<title>{}</title>
+<link rel="stylesheet" type="text/css" href="../style.css"/>
+</head>
+<body>
+{body}
+</body>
+</html>
+"#,
+        escape(title)
+    )
+}
+
+/// EPUB 3 book with one chapter per entry of the selected groups.
+pub struct EpubExporter {
+    groups: Vec<(MangaGroup, GroupEntries)>,
+    cwd: std::path::PathBuf,
+}
+
+impl EpubExporter {
+    pub fn new(mut groups: Vec<(MangaGroup, GroupEntries)>) -> AnyResult<Self> {
+        if groups.is_empty() {
+            anyhow::bail!("No groups selected for the EPUB export.");
+        }
+        for (_, entries) in &mut groups {
+            crate::export_common::sort_entries(entries);
+        }
+
+        Ok(Self {
+            groups,
+            cwd: std::env::current_dir().context("Unable to get CWD.")?,
+        })
+    }
+
+    fn group_title(group: &MangaGroup) -> String {
+        format!("Manga review #{} ({})", group.id, group.added_on)
+    }
+
+    fn book_title(&self) -> String {
+        match self.groups.as_slice() {
+            [(group, _)] => Self::group_title(group),
+            groups => format!(
+                "Manga reviews ({} - {})",
+                groups.iter().map(|(x, _)| x.added_on.date()).min().unwrap(),
+                groups.iter().map(|(x, _)| x.added_on.date()).max().unwrap()
+            ),
+        }
+    }
+
+    fn chapter_file(entry: &MangaEntry) -> String {
+        format!("chapters/entry_{}.xhtml", entry.id)
+    }
+
+    fn group_file(group: &MangaGroup) -> String {
+        format!("chapters/group_{}.xhtml", group.id)
+    }
+
+    fn image_file(image: &MangaImage) -> String {
+        format!("images/{}.jpg", image.id)
+    }
+
+    /// First image in presentation order.
+    fn cover_image(&self) -> Option<&MangaImage> {
+        self.groups
+            .iter()
+            .flat_map(|(_, entries)| entries)
+            .find_map(|(_, images)| images.first())
+    }
+
+    /// Shrinks and re-encodes all images in parallel, keyed by image id.
+    fn prepare_images(&self) -> AnyResult<HashMap<i64, Vec<u8>>> {
+        let images: Vec<&MangaImage> = self
+            .groups
+            .iter()
+            .flat_map(|(_, entries)| entries)
+            .flat_map(|(_, images)| images)
+            .collect();
+
+        images
+            .par_iter()
+            .map(|image| {
+                let (jpeg_data, _, _) = crate::export_common::load_resized_jpeg(
+                    &self.cwd.join(&image.path),
+                    IMAGE_MAX_DIMENSION,
+                    IMAGE_QUALITY,
+                )?;
+                Ok((image.id, jpeg_data))
+            })
+            .collect()
+    }
+
+    fn create_chapter(manga: &MangaEntry, images: &[MangaImage]) -> String {
+        let mut body = String::with_capacity(manga.comment.len() * 2 + 500);
+        writeln!(body, "<h2>{}</h2>", escape(manga.name.trim())).unwrap();
+        writeln!(body, r#"<p class="score">{}/10</p>"#, manga.score).unwrap();
+        body.push_str(&comment_to_xhtml(&manga.comment));
+        for (index, image) in images.iter().enumerate() {
+            writeln!(
+                body,
+                r#"<div class="image"><img src="../{}" alt="{} {}"/></div>"#,
+                Self::image_file(image),
+                escape(manga.name.trim()),
+                index + 1
+            )
+            .unwrap();
+        }
+        xhtml_page(manga.name.trim(), &body)
+    }
+
+    fn create_group_page(group: &MangaGroup, entries: &GroupEntries) -> String {
+        let title = Self::group_title(group);
+        xhtml_page(
+            &title,
+            &format!(
+                "<h1>{}</h1>\n<p class=\"score\">{} titles</p>",
+                escape(&title),
+                entries.len()
+            ),
+        )
+    }
+
+    fn create_cover_page(&self, cover: Option<&MangaImage>) -> String {
+        let image = cover.map_or_else(String::new, |x| {
+            format!(
+                r#"<div class="image"><img src="../{}" alt="Cover"/></div>"#,
+                Self::image_file(x)
+            )
+        });
+        xhtml_page(
+            &self.book_title(),
+            &format!("<h1>{}</h1>\n{image}", escape(&self.book_title())),
+        )
+    }
+
+    fn create_navigation(&self) -> String {
+        let mut list = String::from("<li><a href=\"chapters/cover.xhtml\">Cover</a></li>\n");
+        let single_group = self.groups.len() == 1;
+        for (group, entries) in &self.groups {
+            if !single_group {
+                write!(
+                    list,
+                    r#"<li><a href="{}">{}</a>"#,
+                    Self::group_file(group),
+                    escape(&Self::group_title(group))
+                )
+                .unwrap();
+                // Empty lists aren't allowed in the navigation document
+                list.push_str(if entries.is_empty() {
+                    "</li>\n"
+                } else {
+                    "<ol>\n"
+                });
+            }
+            for (manga, _) in entries {
+                writeln!(
+                    list,
+                    r#"<li><a href="{}">{} ({}/10)</a></li>"#,
+                    Self::chapter_file(manga),
+                    escape(manga.name.trim()),
+                    manga.score
+                )
+                .unwrap();
+            }
+            if !single_group && !entries.is_empty() {
+                list.push_str("</ol></li>\n");
+            }
+        }
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="en" xml:lang="en">
+<head><meta charset="UTF-8"/><title>Contents</title></head>
+<body>
+<nav epub:type="toc" id="toc">
+<h1>Contents</h1>
+<ol>
+{list}</ol>
+</nav>
+</body>
+</html>
+"#
+        )
+    }
+
+    /// Package document; `files` are chapters in reading order after the cover.
+    fn create_package(&self, files: &[String], cover: Option<&MangaImage>) -> String {
+        let mut manifest = String::with_capacity(10_000);
+        let mut spine = String::with_capacity(2_000);
+        for (index, file) in files.iter().enumerate() {
+            writeln!(
+                manifest,
+                r#"<item id="chapter_{index}" href="{file}" media-type="application/xhtml+xml"/>"#
+            )
+            .unwrap();
+            writeln!(spine, r#"<itemref idref="chapter_{index}"/>"#).unwrap();
+        }
+        for image in self
+            .groups
+            .iter()
+            .flat_map(|(_, entries)| entries)
+            .flat_map(|(_, images)| images)
+        {
+            let properties = if cover.is_some_and(|x| x.id == image.id) {
+                r#" properties="cover-image""#
+            } else {
+                ""
+            };
+            writeln!(
+                manifest,
+                r#"<item id="image_{}" href="{}" media-type="image/jpeg"{properties}/>"#,
+                image.id,
+                Self::image_file(image)
+            )
+            .unwrap();
+        }
+        // For EPUB 2 readers that don't know the `cover-image` property
+        let cover_meta = cover.map_or_else(String::new, |x| {
+            format!(r#"<meta name="cover" content="image_{}"/>"#, x.id)
+        });
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book_id" xml:lang="en">
+<metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+<dc:identifier id="book_id">urn:uuid:{}</dc:identifier>
+<dc:title>{}</dc:title>
+<dc:language>en</dc:language>
+<meta property="dcterms:modified">{}</meta>
+{cover_meta}
+</metadata>
+<manifest>
+<item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+<item id="style" href="style.css" media-type="text/css"/>
+<item id="cover" href="chapters/cover.xhtml" media-type="application/xhtml+xml"/>
+{manifest}</manifest>
+<spine>
+<itemref idref="cover"/>
+{spine}</spine>
+</package>
+"#,
+            uuid::Uuid::new_v4(),
+            escape(&self.book_title()),
+            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+        )
+    }
+
+    /// Writes the book into the given file without asking, e.g. to repeat an earlier export.
+    pub fn write_book(&self, path: &std::path::Path) -> AnyResult<()> {
+        let images = self.prepare_images()?;
+        let cover = self.cover_image();
+
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create '{}'.", path.display()))?;
+        let mut zip = zip::ZipWriter::new(std::io::BufWriter::new(file));
+        let deflated = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+
+        // The mimetype has to be the first file and stored uncompressed
+        zip.start_file(
+            "mimetype",
+            zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Stored),
+        )?;
+        zip.write_all(b"application/epub+zip")?;
+
+        zip.start_file("META-INF/container.xml", deflated)?;
+        zip.write_all(
+            br#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+<rootfiles>
+<rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+</rootfiles>
+</container>
+"#,
+        )?;
+
+        zip.start_file("OEBPS/style.css", deflated)?;
+        zip.write_all(STYLESHEET.as_bytes())?;
+        zip.start_file("OEBPS/nav.xhtml", deflated)?;
+        zip.write_all(self.create_navigation().as_bytes())?;
+        zip.start_file("OEBPS/chapters/cover.xhtml", deflated)?;
+        zip.write_all(self.create_cover_page(cover).as_bytes())?;
+
+        let mut files = Vec::with_capacity(100);
+        for (group, entries) in &self.groups {
+            if self.groups.len() > 1 {
+                let file = Self::group_file(group);
+                zip.start_file(format!("OEBPS/{file}"), deflated)?;
+                zip.write_all(Self::create_group_page(group, entries).as_bytes())?;
+                files.push(file);
+            }
+            for (manga, manga_images) in entries {
+                let file = Self::chapter_file(manga);
+                zip.start_file(format!("OEBPS/{file}"), deflated)?;
+                zip.write_all(Self::create_chapter(manga, manga_images).as_bytes())?;
+                files.push(file);
+
+                for image in manga_images {
+                    // JPEG doesn't get any smaller from compression
+                    zip.start_file(
+                        format!("OEBPS/{}", Self::image_file(image)),
+                        zip::write::SimpleFileOptions::default()
+                            .compression_method(zip::CompressionMethod::Stored),
+                    )?;
+                    zip.write_all(&images[&image.id])?;
+                }
+            }
+        }
+
+        zip.start_file("OEBPS/content.opf", deflated)?;
+        zip.write_all(self.create_package(&files, cover).as_bytes())?;
+        zip.finish()?;
+        Ok(())
+    }
+
+    pub fn export_groups(&self) -> AnyResult<Option<std::path::PathBuf>> {
+        let date = chrono::Local::now().date_naive();
+        let file_name = match self.groups.as_slice() {
+            [(group, _)] => format!("{}_{}.epub", date, group.id),
+            _ => format!("{date}_reviews.epub"),
+        };
+
+        let export_path = rfd::FileDialog::new()
+            .set_title("Select EPUB export destination")
+            .set_directory(&self.cwd)
+            .add_filter("EPUB book", &["epub"])
+            .set_file_name(file_name)
+            .save_file();
+
+        let Some(export_path) = export_path else {
+            return Ok(None);
+        };
+        self.write_book(&export_path)?;
+        Ok(Some(export_path))
+    }
+}
@@ -0,0 +1,331 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::image_export::ImageExportOptions;
+use crate::site_export::SiteLayout;
+use crate::text_export::TextExportFormat;
+use shared::types::{MangaEntry, MangaImage};
+
+/// Sorts entries the way they're presented: by score, then by name.
+pub fn sort_entries(entries: &mut [(MangaEntry, Vec<MangaImage>)]) {
+    entries.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+    entries.sort_by_key(|a| a.0.score);
+}
+
+/// Loads an image, shrinks it to fit into `max_dimension` and encodes it as JPEG.
+///
+/// Returns JPEG data along with the resulting width and height.
+pub fn load_resized_jpeg(
+    path: &Path,
+    max_dimension: u32,
+    quality: u8,
+) -> AnyResult<(Vec<u8>, u32, u32)> {
+    let file_contents = std::fs::read(path)
+        .with_context(|| format!("Failed to read image '{}'.", path.display()))?;
+    let mut image = image::load_from_memory(&file_contents)
+        .with_context(|| format!("Failed to decode image '{}'.", path.display()))?;
+    if image.width().max(image.height()) > max_dimension {
+        image = image.resize(
+            max_dimension,
+            max_dimension,
+            image::imageops::FilterType::Lanczos3,
+        );
+    }
+
+    let mut jpeg_data = Vec::with_capacity(500_000);
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg_data, quality)
+        .encode_image(&image.to_rgb8())?;
+    Ok((jpeg_data, image.width(), image.height()))
+}
+
+/// How much exported images are shrunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImagePreset {
+    /// Stored files copied as they are
+    Original,
+    /// Good enough for full-screen viewing
+    Web,
+    /// Small previews for chats and slow connections
+    Tiny,
+}
+
+impl ImagePreset {
+    pub const ALL: [Self; 3] = [Self::Original, Self::Web, Self::Tiny];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Original => "Original",
+            Self::Web => "Web (1920px)",
+            Self::Tiny => "Tiny (800px)",
+        }
+    }
+
+    fn max_dimension(self) -> Option<u32> {
+        match self {
+            Self::Original => None,
+            Self::Web => Some(1920),
+            Self::Tiny => Some(800),
+        }
+    }
+
+    pub fn default_quality(self) -> u8 {
+        match self {
+            Self::Original => 95,
+            Self::Web => 82,
+            Self::Tiny => 70,
+        }
+    }
+}
+
+/// Format images are encoded to, both by exports and when they're added to an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageOutputFormat {
+    Jpeg,
+    /// Lossless, quality doesn't apply to it.
+    Png,
+    /// Lossy, through `libwebp`: the `image` crate only writes lossless WebP,
+    /// which usually ends up larger than the JPEG it replaces.
+    WebP,
+}
+
+impl ImageOutputFormat {
+    pub const ALL: [Self; 3] = [Self::Jpeg, Self::Png, Self::WebP];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Jpeg => "JPEG",
+            Self::Png => "PNG (lossless)",
+            Self::WebP => "WebP",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::WebP => "webp",
+        }
+    }
+
+    pub fn has_quality(self) -> bool {
+        self != Self::Png
+    }
+}
+
+/// Settings for the images written by file exports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportImageOptions {
+    pub preset: ImagePreset,
+    pub format: ImageOutputFormat,
+    /// JPEG and WebP quality, 1..=100
+    pub quality: u8,
+}
+
+impl Default for ExportImageOptions {
+    fn default() -> Self {
+        Self {
+            preset: ImagePreset::Original,
+            format: ImageOutputFormat::Jpeg,
+            quality: ImagePreset::Original.default_quality(),
+        }
+    }
+}
+
+/// How a group was exported, stored as JSON in the export history so it can be repeated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum ExportKind {
+    /// Handlebars template, its name is kept in the `template` column.
+    Template(ExportImageOptions),
+    Markdown {
+        front_matter: bool,
+        image_options: ExportImageOptions,
+    },
+    Pdf,
+    /// Book made from several groups, the export is listed in the history of each.
+    Epub {
+        groups: Vec<i64>,
+    },
+    Image(ImageExportOptions),
+    /// Site with every group, listed in the history of each.
+    Site {
+        layout: SiteLayout,
+        image_options: ExportImageOptions,
+    },
+    /// Text is only shown, so these exports have no path.
+    Text {
+        format: TextExportFormat,
+    },
+}
+
+impl ExportKind {
+    /// Options of an export, older exports only stored the image options of a template.
+    pub fn parse(options: &str) -> AnyResult<Self> {
+        serde_json::from_str(options)
+            .or_else(|_| serde_json::from_str(options).map(Self::Template))
+            .context("Options of the export are corrupted.")
+    }
+}
+
+impl ExportImageOptions {
+    /// Output file name made from the hash of the source file and the options,
+    /// so that unchanged images aren't processed again by repeated exports.
+    fn output_file_name(&self, source_path: &Path, contents: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(contents);
+        let extension = match self.preset.max_dimension() {
+            None => source_path
+                .extension()
+                .map_or_else(String::new, |x| x.to_string_lossy().to_lowercase()),
+            Some(max_dimension) => {
+                hasher.update(format!(
+                    "{max_dimension}:{:?}:{}",
+                    self.format, self.quality
+                ));
+                self.format.extension().to_string()
+            }
+        };
+        let hash: String = hasher
+            .finalize()
+            .iter()
+            .take(12)
+            .map(|x| format!("{x:02x}"))
+            .collect();
+        format!("{hash}.{extension}")
+    }
+
+    fn write_image(&self, contents: &[u8], destination: &Path) -> AnyResult<()> {
+        let Some(max_dimension) = self.preset.max_dimension() else {
+            return write_file_atomically(destination, contents);
+        };
+
+        let mut image = image::load_from_memory(contents)?;
+        if image.width().max(image.height()) > max_dimension {
+            image = image.resize(
+                max_dimension,
+                max_dimension,
+                image::imageops::FilterType::Lanczos3,
+            );
+        }
+        write_file_atomically(
+            destination,
+            &encode_image(&image, self.format, self.quality)?,
+        )
+    }
+}
+
+/// Encodes the image, `quality` is from 1 to 100.
+pub fn encode_image(
+    image: &image::DynamicImage,
+    format: ImageOutputFormat,
+    quality: u8,
+) -> AnyResult<Vec<u8>> {
+    let quality = quality.clamp(1, 100);
+    let mut contents = Vec::with_capacity(500_000);
+    match format {
+        ImageOutputFormat::Jpeg => {
+            image::codecs::jpeg::JpegEncoder::new_with_quality(&mut contents, quality)
+                .encode_image(&image.to_rgb8())?;
+        }
+        ImageOutputFormat::Png => {
+            let encoder = image::codecs::png::PngEncoder::new(&mut contents);
+            // PNG has no floating point samples, HDR images are stored as 16 bit
+            match image.color() {
+                image::ColorType::Rgb32F | image::ColorType::Rgba32F => {
+                    image::DynamicImage::ImageRgba16(image.to_rgba16())
+                        .write_with_encoder(encoder)?;
+                }
+                _ => image.write_with_encoder(encoder)?,
+            }
+        }
+        ImageOutputFormat::WebP => {
+            let pixels = image.to_rgba8();
+            let encoded = webp::Encoder::from_rgba(&pixels, pixels.width(), pixels.height())
+                .encode(f32::from(quality));
+            contents.extend_from_slice(&encoded);
+        }
+    }
+    Ok(contents)
+}
+
+/// Writes the file under a temporary name first, so an interrupted write
+/// doesn't leave a broken file in place of the old one or for later exports to reuse.
+pub fn write_file_atomically(destination: &Path, contents: &[u8]) -> AnyResult<()> {
+    let mut temporary_name = destination.file_name().unwrap_or_default().to_owned();
+    temporary_name.push(".tmp");
+    let temporary_path = destination.with_file_name(temporary_name);
+    std::fs::write(&temporary_path, contents)
+        .with_context(|| format!("Failed to write '{}'.", temporary_path.display()))?;
+    std::fs::rename(&temporary_path, destination)
+        .with_context(|| format!("Failed to replace '{}'.", destination.display()))?;
+    Ok(())
+}
+
+/// Writes all images of the group into `media/review_{group_id}` inside `export_folder`,
+/// processed according to `options`. Images are handled in parallel and files left
+/// by previous exports with the same options are reused.
+///
+/// Returns image ids mapped to paths relative to `export_folder`, always with `/` separators.
+pub fn copy_group_images(
+    cwd: &Path,
+    export_folder: &Path,
+    group_id: i64,
+    entries: &[(MangaEntry, Vec<MangaImage>)],
+    options: &ExportImageOptions,
+) -> AnyResult<HashMap<i64, String>> {
+    let relative_folder_to = format!("media/review_{group_id}");
+    let full_folder_to = export_folder.join(&relative_folder_to);
+    if !full_folder_to.exists() {
+        std::fs::create_dir_all(&full_folder_to)?;
+    }
+
+    let images: Vec<&MangaImage> = entries.iter().flat_map(|(_, images)| images).collect();
+    // Contents are only kept for images whose output file doesn't exist yet
+    let outputs = images
+        .par_iter()
+        .map(|image| {
+            let full_path_from = cwd.join(&image.path);
+            let contents = std::fs::read(&full_path_from)
+                .with_context(|| format!("Failed to read image '{}'.", full_path_from.display()))?;
+            let filename = options.output_file_name(&full_path_from, &contents);
+            let contents = (!full_folder_to.join(&filename).exists()).then_some(contents);
+            Ok((image.id, filename, contents))
+        })
+        .collect::<AnyResult<Vec<_>>>()?;
+
+    // Images with the same contents share an output file, which is written only once,
+    // otherwise parallel writes of it would race for the same temporary file
+    let mut sources = HashMap::new();
+    for (image, (_, filename, contents)) in images.iter().zip(&outputs) {
+        if let Some(contents) = contents {
+            sources
+                .entry(filename.as_str())
+                .or_insert((*image, contents));
+        }
+    }
+    sources
+        .into_par_iter()
+        .map(|(filename, (image, contents))| {
+            options
+                .write_image(contents, &full_folder_to.join(filename))
+                .with_context(|| {
+                    format!(
+                        "Failed to export image '{}'.",
+                        cwd.join(&image.path).display()
+                    )
+                })
+        })
+        .collect::<AnyResult<()>>()?;
+
+    Ok(outputs
+        .into_iter()
+        .map(|(id, filename, _)| (id, format!("{relative_folder_to}/{filename}")))
+        .collect())
+}
@@ -0,0 +1,142 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+use handlebars::{
+    Handlebars, Helper, HelperDef, RenderContext, RenderError, RenderErrorReason, ScopedJson,
+    handlebars_helper,
+};
+
+use crate::manga_group_export::TEMPLATES_FOLDER;
+
+/// Highest score an entry can get in the GUI.
+const MAX_SCORE: i64 = 10;
+
+// {{stars score}} -> "★★★★★★★☆☆☆", {{stars score max=5}} rescales to 5 stars
+handlebars_helper!(stars: |score: i64, { max: i64 = 10 }| {
+    let max = max.max(1);
+    let filled = (score.clamp(0, MAX_SCORE) * max + MAX_SCORE / 2) / MAX_SCORE;
+    let filled = usize::try_from(filled).unwrap_or_default();
+    let empty = usize::try_from(max).unwrap_or_default() - filled;
+    format!("{}{}", "★".repeat(filled), "☆".repeat(empty))
+});
+
+// {{score_color score}} -> "#rrggbb", going from red (1) through yellow to green (10)
+handlebars_helper!(score_color: |score: i64| {
+    #[allow(clippy::cast_precision_loss)]
+    let position = (score.clamp(1, MAX_SCORE) - 1) as f64 / (MAX_SCORE - 1) as f64;
+    let (red, green) = if position < 0.5 {
+        (255., 255. * position * 2.)
+    } else {
+        (255. * (1. - position) * 2., 255.)
+    };
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let result = format!("#{:02x}{:02x}00", red.round() as u8, green.round() as u8);
+    result
+});
+
+// {{markdown comment}} -> comment rendered as HTML
+handlebars_helper!(markdown: |text: str| {
+    let parser = pulldown_cmark::Parser::new_ext(text, pulldown_cmark::Options::all());
+    let mut result = String::with_capacity(text.len() * 2);
+    pulldown_cmark::html::push_html(&mut result, parser);
+    result
+});
+
+// {{pluralize image_count "image"}} -> "1 image" / "3 images",
+// irregular plurals are passed explicitly: {{pluralize count "entry" plural="entries"}}
+handlebars_helper!(pluralize: |count: i64, singular: str, { plural: str = "" }| {
+    let word = match (count, plural) {
+        (1, _) => singular.to_string(),
+        (_, "") => format!("{singular}s"),
+        (_, plural) => plural.to_string(),
+    };
+    format!("{count} {word}")
+});
+
+/// `{{format_date group.added_on "%d %B %Y"}}`, format defaults to `%Y-%m-%d`.
+///
+/// Accepts dates in the `YYYY-MM-DD HH:MM:SS` form used by the export data.
+struct FormatDate;
+
+impl HelperDef for FormatDate {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc handlebars::Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        use std::fmt::Write;
+
+        let date = h
+            .param(0)
+            .and_then(|x| x.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("format_date", 0))?;
+        let format = h
+            .param(1)
+            .and_then(|x| x.value().as_str())
+            .unwrap_or("%Y-%m-%d");
+
+        let date = chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S%.f")
+            .map_err(|e| RenderErrorReason::Other(format!("Invalid date '{date}': {e}")))?;
+        let mut result = String::new();
+        write!(result, "{}", date.format(format))
+            .map_err(|_| RenderErrorReason::Other(format!("Invalid date format '{format}'")))?;
+
+        Ok(ScopedJson::Derived(handlebars::JsonValue::from(result)))
+    }
+}
+
+/// Rhai engine for user helpers: no file or network access is available
+/// to scripts, and runaway scripts are cut off by the limits below.
+fn create_script_engine() -> rhai::Engine {
+    let mut engine = rhai::Engine::new();
+    // `import` would otherwise load scripts from anywhere on disk,
+    // and `print`/`debug` would write to the console
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine.on_print(|_| {});
+    engine.on_debug(|_, _, _| {});
+    engine.set_max_operations(100_000);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(1_000_000);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(10_000);
+    engine.disable_symbol("eval");
+    engine
+}
+
+/// Registers built-in helpers, then every `*.rhai` script from the
+/// `templates/helpers` folder under its file stem.
+///
+/// Scripts get helper parameters as the `params` array and hash as the `hash` map,
+/// e.g. `templates/helpers/shout.rhai` containing `params[0].to_upper() + "!"`
+/// can be used as `{{shout name}}`.
+pub fn register_helpers(handlebars: &mut Handlebars, cwd: &std::path::Path) -> AnyResult<()> {
+    handlebars.register_helper("stars", Box::new(stars));
+    handlebars.register_helper("score_color", Box::new(score_color));
+    handlebars.register_helper("markdown", Box::new(markdown));
+    handlebars.register_helper("pluralize", Box::new(pluralize));
+    handlebars.register_helper("format_date", Box::new(FormatDate));
+
+    let Ok(contents) = std::fs::read_dir(cwd.join(TEMPLATES_FOLDER).join("helpers")) else {
+        return Ok(());
+    };
+
+    handlebars.set_engine(create_script_engine());
+    for entry in contents.filter_map(Result::ok) {
+        let path = entry.path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "rhai") {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        handlebars
+            .register_script_helper_file(&name, &path)
+            .with_context(|| format!("Helper script '{}' is invalid.", path.display()))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::types::SCORE_RANGE;
+use shared::types::{MangaEntry, MangaGroup, MangaImage};
+
+/// What to do with the group once the checks pass or the user dismisses them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintAction {
+    Export,
+    WebShare,
+}
+
+impl LintAction {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Export => "Export anyway",
+            Self::WebShare => "Share anyway",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintProblemKind {
+    EmptyName,
+    ScoreOutOfRange(i64),
+    EmptyComment,
+    NoImages,
+    UnsavedEdits,
+    MissingFile(String),
+}
+
+impl LintProblemKind {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::EmptyName => "name is empty".to_string(),
+            Self::ScoreOutOfRange(score) => format!(
+                "score {score} is outside of {}..={}",
+                SCORE_RANGE.start(),
+                SCORE_RANGE.end()
+            ),
+            Self::EmptyComment => "comment is empty".to_string(),
+            Self::NoImages => "has no images".to_string(),
+            Self::UnsavedEdits => "has unsaved changes".to_string(),
+            Self::MissingFile(path) => format!("image '{path}' is missing"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LintProblem {
+    pub entry_id: i64,
+    pub entry_name: String,
+    pub kind: LintProblemKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct LintReport {
+    pub group: MangaGroup,
+    pub action: LintAction,
+    pub problems: Vec<LintProblem>,
+}
+
+/// Checks entries as they are shown in the GUI, comparing them with the stored ones
+/// to find unsaved edits.
+pub fn lint_group(
+    gui_entries: &[MangaEntry],
+    stored_entries: &[(MangaEntry, Vec<MangaImage>)],
+    cwd: &Path,
+) -> Vec<LintProblem> {
+    let stored: HashMap<i64, &(MangaEntry, Vec<MangaImage>)> =
+        stored_entries.iter().map(|x| (x.0.id, x)).collect();
+
+    let mut result = Vec::new();
+    for entry in gui_entries {
+        let mut add_problem = |kind| {
+            result.push(LintProblem {
+                entry_id: entry.id,
+                entry_name: entry.name.trim().to_string(),
+                kind,
+            });
+        };
+
+        if entry.name.trim().is_empty() {
+            add_problem(LintProblemKind::EmptyName);
+        }
+        if !SCORE_RANGE.contains(&entry.score) {
+            add_problem(LintProblemKind::ScoreOutOfRange(entry.score));
+        }
+        if entry.comment.trim().is_empty() {
+            add_problem(LintProblemKind::EmptyComment);
+        }
+
+        let Some((stored_entry, images)) = stored.get(&entry.id) else {
+            continue;
+        };
+        if stored_entry.name != entry.name
+            || stored_entry.score != entry.score
+            || stored_entry.comment != entry.comment
+        {
+            add_problem(LintProblemKind::UnsavedEdits);
+        }
+        if images.is_empty() {
+            add_problem(LintProblemKind::NoImages);
+        }
+        for image in images {
+            if !cwd.join(&image.path).is_file() {
+                add_problem(LintProblemKind::MissingFile(image.path.clone()));
+            }
+        }
+    }
+
+    result
+}
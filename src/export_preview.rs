@@ -0,0 +1,102 @@
+/// Slides of a rendered slideshow, as reveal.js would show them.
+#[derive(Debug, Clone)]
+pub struct ExportPreview {
+    pub template: String,
+    pub slides: Vec<PreviewSlide>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PreviewSlide {
+    pub heading: String,
+    pub paragraphs: Vec<String>,
+    pub images: Vec<PreviewImage>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PreviewImage {
+    /// `src` of the image, the stored image path when rendered for preview.
+    pub src: String,
+    /// Fragment classes besides `fragment`, e.g. `fade-in-then-out`; `None` for images
+    /// shown together with the slide.
+    pub fragment: Option<String>,
+    /// Step of the slide at which the fragment plays.
+    pub step: Option<usize>,
+}
+
+fn selector(selector: &str) -> scraper::Selector {
+    scraper::Selector::parse(selector).unwrap()
+}
+
+fn element_text(element: scraper::ElementRef) -> String {
+    element
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Splits rendered HTML into slides; fragments without `data-fragment-index`
+/// get the next free step, like reveal.js does.
+pub fn build_preview(template: &str, html: &str) -> ExportPreview {
+    let document = scraper::Html::parse_document(html);
+
+    let mut slides = Vec::new();
+    for section in document.select(&selector("section")) {
+        // Vertical stacks are sections of sections, only the inner ones are slides
+        if section.select(&selector("section")).next().is_some() {
+            continue;
+        }
+
+        let heading = section
+            .select(&selector("h1, h2, h3, h4"))
+            .next()
+            .map(element_text)
+            .unwrap_or_default();
+        let paragraphs = section
+            .select(&selector("p"))
+            .map(element_text)
+            .filter(|x| !x.is_empty())
+            .collect();
+
+        let mut next_step = 0;
+        let mut images = Vec::new();
+        for image in section.select(&selector("img")) {
+            let element = image.value();
+            let is_fragment = element.classes().any(|x| x == "fragment");
+            let step = if is_fragment {
+                let step = element
+                    .attr("data-fragment-index")
+                    .and_then(|x| x.parse().ok())
+                    .unwrap_or(next_step);
+                next_step = next_step.max(step + 1);
+                Some(step)
+            } else {
+                None
+            };
+
+            images.push(PreviewImage {
+                src: element.attr("src").unwrap_or_default().to_string(),
+                fragment: is_fragment.then(|| {
+                    element
+                        .classes()
+                        .filter(|x| *x != "fragment")
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                }),
+                step,
+            });
+        }
+
+        slides.push(PreviewSlide {
+            heading,
+            paragraphs,
+            images,
+        });
+    }
+
+    ExportPreview {
+        template: template.to_string(),
+        slides,
+    }
+}
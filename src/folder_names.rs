@@ -0,0 +1,226 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use shared::types::{MangaEntry, MangaGroup};
+
+static BRACKETS_REGEX: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"\[[^\]]*\]|\([^)]*\)|\{[^}]*\}").unwrap());
+static VOLUME_CHAPTER_REGEX: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r"(?i)\b(?:v|vol|volume|c|ch|chap|chapter|ep|episode)\.?\s*\d+(?:\.\d+)?(?:\s*-\s*\d+(?:\.\d+)?)?\b",
+    )
+    .unwrap()
+});
+
+/// How folder names are turned into entry names and matched with existing entries.
+#[derive(Debug, Clone)]
+pub struct NameCleanupRules {
+    /// Removes `[tags]`, `(tags)` and `{tags}` anywhere in the name.
+    pub strip_brackets: bool,
+    /// Removes markers like `v01`, `Vol. 2`, `ch.15` or `c001-010`.
+    pub strip_volume_chapter: bool,
+    /// Release group names, one per line, removed from the start of folder names.
+    pub release_groups: String,
+    /// Lowest similarity, from 0 to 1, at which a folder is matched with an existing entry.
+    pub similarity_threshold: f64,
+    /// How deep to look for title folders; 1 means only direct subfolders.
+    pub max_depth: usize,
+    /// Glob patterns, one per line, of folders to skip, e.g. `Extras` or `*/Scans`.
+    pub ignore_globs: String,
+}
+
+impl Default for NameCleanupRules {
+    fn default() -> Self {
+        Self {
+            strip_brackets: true,
+            strip_volume_chapter: true,
+            release_groups: String::new(),
+            similarity_threshold: 0.8,
+            max_depth: 1,
+            ignore_globs: String::new(),
+        }
+    }
+}
+
+impl NameCleanupRules {
+    pub fn clean_name(&self, name: &str) -> String {
+        let mut name = name.replace('_', " ");
+
+        for group in self.release_groups.lines().map(str::trim) {
+            if group.is_empty() {
+                continue;
+            }
+            // Only whole words, so "Ta" doesn't cut the start of "Tales of X"
+            if let Some(prefix) = name.get(..group.len())
+                && prefix.eq_ignore_ascii_case(group)
+            {
+                let rest = &name[group.len()..];
+                if rest.is_empty()
+                    || rest.starts_with([' ', '-', '.'])
+                    || !group.ends_with(char::is_alphanumeric)
+                {
+                    name = rest.trim_start_matches([' ', '-', '.']).to_string();
+                }
+            }
+        }
+        if self.strip_brackets {
+            name = BRACKETS_REGEX.replace_all(&name, " ").to_string();
+        }
+        if self.strip_volume_chapter {
+            name = VOLUME_CHAPTER_REGEX.replace_all(&name, " ").to_string();
+        }
+
+        name.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_matches([' ', '-', '.', ',', '–'])
+            .to_string()
+    }
+
+    /// Similarity of two names after cleanup, from 0 to 1.
+    pub fn similarity(&self, a: &str, b: &str) -> f64 {
+        strsim::normalized_levenshtein(
+            &self.clean_name(a).to_lowercase(),
+            &self.clean_name(b).to_lowercase(),
+        )
+    }
+
+    fn ignore_set(&self) -> AnyResult<globset::GlobSet> {
+        let mut builder = globset::GlobSetBuilder::new();
+        for pattern in self.ignore_globs.lines().map(str::trim) {
+            if pattern.is_empty() {
+                continue;
+            }
+            builder.add(
+                globset::Glob::new(pattern)
+                    .with_context(|| format!("Invalid ignore pattern '{pattern}'."))?,
+            );
+        }
+        Ok(builder.build()?)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FolderName {
+    /// Path of the folder, relative to the scanned one.
+    pub folder: PathBuf,
+    pub name: String,
+}
+
+/// Title folders of `root`: subfolders at `max_depth`, or without subfolders of their own.
+pub fn scan_folder(root: &Path, rules: &NameCleanupRules) -> AnyResult<Vec<FolderName>> {
+    let ignore = rules.ignore_set()?;
+    let mut result = Vec::new();
+    scan_level(root, Path::new(""), 1, rules, &ignore, &mut result)?;
+    Ok(result)
+}
+
+fn subfolders(folder: &Path, relative: &Path, ignore: &globset::GlobSet) -> Vec<PathBuf> {
+    let Ok(contents) = std::fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    let mut result = contents
+        .filter_map(Result::ok)
+        .filter(|x| x.path().is_dir())
+        .map(|x| relative.join(x.file_name()))
+        .filter(|x| !ignore.is_match(x) && !x.file_name().is_some_and(|x| ignore.is_match(x)))
+        .collect::<Vec<_>>();
+    result.sort_by(|a, b| natord::compare(&a.to_string_lossy(), &b.to_string_lossy()));
+    result
+}
+
+fn scan_level(
+    root: &Path,
+    relative: &Path,
+    depth: usize,
+    rules: &NameCleanupRules,
+    ignore: &globset::GlobSet,
+    result: &mut Vec<FolderName>,
+) -> AnyResult<()> {
+    let folder = root.join(relative);
+    if depth == 1 {
+        std::fs::read_dir(&folder)
+            .with_context(|| format!("Failed to read folder '{}'.", folder.display()))?;
+    }
+
+    for subfolder in subfolders(&folder, relative, ignore) {
+        if depth < rules.max_depth
+            && !subfolders(&root.join(&subfolder), &subfolder, ignore).is_empty()
+        {
+            scan_level(root, &subfolder, depth + 1, rules, ignore, result)?;
+            continue;
+        }
+
+        let folder_name = subfolder.file_name().unwrap().to_string_lossy().to_string();
+        let name = rules.clean_name(&folder_name);
+        result.push(FolderName {
+            folder: subfolder,
+            name: if name.is_empty() { folder_name } else { name },
+        });
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct ExistingMatch {
+    pub entry_id: i64,
+    pub entry_name: String,
+    pub similarity: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct NameMatch {
+    pub folder: PathBuf,
+    /// Name of the entry to create, can be edited in the review.
+    pub name: String,
+    pub existing: Option<ExistingMatch>,
+    pub create: bool,
+}
+
+impl NameMatch {
+    pub fn is_exact(&self) -> bool {
+        self.existing.as_ref().is_some_and(|x| x.similarity >= 1.)
+    }
+}
+
+/// Folder names to review before entries are created from them.
+#[derive(Debug)]
+pub struct FolderNameReview {
+    pub group: MangaGroup,
+    pub folder: PathBuf,
+    pub matches: Vec<NameMatch>,
+}
+
+/// Pairs every folder with the most similar existing entry. Folders without a match are
+/// marked to be created, folders cleaned up to the same name are only listed once.
+pub fn match_names(
+    folders: Vec<FolderName>,
+    entries: &[MangaEntry],
+    rules: &NameCleanupRules,
+) -> Vec<NameMatch> {
+    let mut seen_names = std::collections::HashSet::new();
+    folders
+        .into_iter()
+        .filter(|x| seen_names.insert(x.name.to_lowercase()))
+        .map(|folder| {
+            let existing = entries
+                .iter()
+                .map(|entry| ExistingMatch {
+                    entry_id: entry.id,
+                    entry_name: entry.name.clone(),
+                    similarity: rules.similarity(&folder.name, &entry.name),
+                })
+                .max_by(|a, b| a.similarity.total_cmp(&b.similarity))
+                .filter(|x| x.similarity >= rules.similarity_threshold);
+            NameMatch {
+                folder: folder.folder,
+                name: folder.name,
+                create: existing.is_none(),
+                existing,
+            }
+        })
+        .collect()
+}
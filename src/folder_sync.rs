@@ -0,0 +1,239 @@
+use anyhow::Result as AnyResult;
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::folder_names::{FolderName, NameCleanupRules};
+use shared::types::{MangaEntry, MangaGroup};
+
+/// What happens to an entry whose folder is gone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VanishedAction {
+    Keep,
+    Flag,
+    Delete,
+}
+
+impl VanishedAction {
+    pub const ALL: [Self; 3] = [Self::Keep, Self::Flag, Self::Delete];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Keep => "Keep",
+            Self::Flag => "Flag as missing",
+            Self::Delete => "Delete entry",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SyncChange {
+    /// New folder, an entry is created for it.
+    Add { folder: PathBuf, name: String },
+    /// Entry without a source gets the most similar new folder.
+    Link {
+        entry: MangaEntry,
+        folder: PathBuf,
+        similarity: f64,
+    },
+    /// Folder of the entry vanished and a similar new one appeared.
+    Rename {
+        entry: MangaEntry,
+        folder: PathBuf,
+        name: String,
+        similarity: f64,
+    },
+    Vanished {
+        entry: MangaEntry,
+        action: VanishedAction,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct SyncItem {
+    pub change: SyncChange,
+    pub apply: bool,
+}
+
+/// Differences between a group and its source folder, applied together.
+#[derive(Debug)]
+pub struct FolderSyncPlan {
+    pub group: MangaGroup,
+    pub folder: PathBuf,
+    pub items: Vec<SyncItem>,
+    /// Flagged entries whose folders are back, the flag is cleared with the rest.
+    pub restored: Vec<MangaEntry>,
+}
+
+fn folder_name(path: &Path) -> String {
+    path.file_name()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Compares title folders found in `folder` with the entries of the group.
+pub fn plan_sync(
+    group: MangaGroup,
+    folder: &Path,
+    entries: Vec<MangaEntry>,
+    rules: &NameCleanupRules,
+) -> AnyResult<FolderSyncPlan> {
+    let found = crate::folder_names::scan_folder(folder, rules)?;
+    let found_paths = found
+        .iter()
+        .map(|x| folder.join(&x.folder))
+        .collect::<HashSet<_>>();
+
+    let mut linked_paths = HashSet::new();
+    let mut vanished = Vec::new();
+    let mut unlinked = Vec::new();
+    let mut restored = Vec::new();
+    for entry in entries {
+        match entry.source_path.as_ref().map(PathBuf::from) {
+            Some(path) if found_paths.contains(&path) => {
+                linked_paths.insert(path);
+                if entry.missing_from_source {
+                    restored.push(entry);
+                }
+            }
+            // Entries imported from somewhere else aren't touched
+            Some(path) if path.starts_with(folder) => vanished.push(entry),
+            Some(_) => {}
+            None => unlinked.push(entry),
+        }
+    }
+
+    let new_folders = found
+        .into_iter()
+        .filter(|x| !linked_paths.contains(&folder.join(&x.folder)))
+        .collect::<Vec<_>>();
+
+    let mut items = Vec::new();
+    let mut taken_folders = HashSet::new();
+
+    // Entries added by name only are linked to the most similar folders
+    let link_pairs = best_pairs(
+        &unlinked,
+        &new_folders,
+        &taken_folders,
+        rules.similarity_threshold,
+        |entry, found| rules.similarity(&entry.name, &found.name),
+    );
+    for (similarity, entry_index, folder_index) in link_pairs {
+        taken_folders.insert(folder_index);
+        items.push(SyncItem {
+            change: SyncChange::Link {
+                entry: unlinked[entry_index].clone(),
+                folder: folder.join(&new_folders[folder_index].folder),
+                similarity,
+            },
+            apply: true,
+        });
+    }
+
+    let rename_pairs = best_pairs(
+        &vanished,
+        &new_folders,
+        &taken_folders,
+        rules.similarity_threshold,
+        |entry, found| {
+            let old_name = folder_name(Path::new(entry.source_path.as_ref().unwrap()));
+            rules
+                .similarity(&old_name, &folder_name(&found.folder))
+                .max(rules.similarity(&entry.name, &found.name))
+        },
+    );
+    let mut renamed_entries = HashSet::new();
+    for (similarity, entry_index, folder_index) in rename_pairs {
+        renamed_entries.insert(entry_index);
+        taken_folders.insert(folder_index);
+
+        let entry = vanished[entry_index].clone();
+        let new_folder = &new_folders[folder_index];
+        // Names that were edited by hand are kept
+        let old_name = folder_name(Path::new(entry.source_path.as_ref().unwrap()));
+        let name = if entry.name == rules.clean_name(&old_name) {
+            new_folder.name.clone()
+        } else {
+            entry.name.clone()
+        };
+        items.push(SyncItem {
+            change: SyncChange::Rename {
+                entry,
+                folder: folder.join(&new_folder.folder),
+                name,
+                similarity,
+            },
+            apply: true,
+        });
+    }
+
+    for (index, entry) in vanished.into_iter().enumerate() {
+        if renamed_entries.contains(&index) {
+            continue;
+        }
+        items.push(SyncItem {
+            change: SyncChange::Vanished {
+                entry,
+                action: VanishedAction::Flag,
+            },
+            apply: true,
+        });
+    }
+
+    for (index, new_folder) in new_folders.into_iter().enumerate() {
+        if taken_folders.contains(&index) {
+            continue;
+        }
+        items.push(SyncItem {
+            change: SyncChange::Add {
+                folder: folder.join(new_folder.folder),
+                name: new_folder.name,
+            },
+            apply: true,
+        });
+    }
+
+    Ok(FolderSyncPlan {
+        group,
+        folder: folder.to_path_buf(),
+        items,
+        restored,
+    })
+}
+
+/// Entry and folder indices scoring at least `threshold`, most similar pairs first,
+/// using every entry and folder at most once.
+fn best_pairs(
+    entries: &[MangaEntry],
+    folders: &[FolderName],
+    taken_folders: &HashSet<usize>,
+    threshold: f64,
+    similarity: impl Fn(&MangaEntry, &FolderName) -> f64,
+) -> Vec<(f64, usize, usize)> {
+    let mut pairs = Vec::new();
+    for (entry_index, entry) in entries.iter().enumerate() {
+        for (folder_index, found) in folders.iter().enumerate() {
+            if taken_folders.contains(&folder_index) {
+                continue;
+            }
+            let similarity = similarity(entry, found);
+            if similarity >= threshold {
+                pairs.push((similarity, entry_index, folder_index));
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut used_entries = HashSet::new();
+    let mut used_folders = HashSet::new();
+    pairs.retain(|(_, entry_index, folder_index)| {
+        if used_entries.contains(entry_index) || used_folders.contains(folder_index) {
+            return false;
+        }
+        used_entries.insert(*entry_index);
+        used_folders.insert(*folder_index);
+        true
+    });
+    pairs
+}
@@ -0,0 +1,108 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+
+use std::path::PathBuf;
+
+/// Environment variable (can be set in `.env`) with the path of the font used by
+/// PDF and image exports. It should have Japanese glyphs and TrueType outlines.
+pub const EXPORT_FONT_VARIABLE: &str = "EXPORT_FONT";
+
+/// Fonts with Japanese glyphs and TrueType outlines usually present on each platform.
+const FONT_CANDIDATES: &[&str] = &[
+    #[cfg(windows)]
+    r"C:\Windows\Fonts\YuGothM.ttc",
+    #[cfg(windows)]
+    r"C:\Windows\Fonts\meiryo.ttc",
+    #[cfg(windows)]
+    r"C:\Windows\Fonts\msgothic.ttc",
+    #[cfg(target_os = "macos")]
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    #[cfg(target_os = "macos")]
+    "/Library/Fonts/Arial Unicode.ttf",
+    #[cfg(all(unix, not(target_os = "macos")))]
+    "/usr/share/fonts/opentype/ipaexfont-gothic/ipaexg.ttf",
+    #[cfg(all(unix, not(target_os = "macos")))]
+    "/usr/share/fonts/truetype/fonts-japanese-gothic.ttf",
+    #[cfg(all(unix, not(target_os = "macos")))]
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    #[cfg(all(unix, not(target_os = "macos")))]
+    "/usr/share/fonts/google-droid-sans-fonts/DroidSansFallbackFull.ttf",
+];
+
+fn find_font_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(EXPORT_FONT_VARIABLE) {
+        return Some(PathBuf::from(path));
+    }
+
+    if let Some(path) = FONT_CANDIDATES
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.exists())
+    {
+        return Some(path);
+    }
+
+    rfd::FileDialog::new()
+        .set_title("Select a TrueType font with Japanese glyphs")
+        .add_filter("TrueType font", &["ttf", "ttc"])
+        .pick_file()
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+/// Copies the first font of a TrueType collection (`.ttc`) into a standalone font file,
+/// since PDF can only embed single fonts.
+fn extract_first_font(collection: &[u8]) -> Option<Vec<u8>> {
+    let font_offset = read_u32(collection, 12)? as usize;
+    let table_count = read_u16(collection, font_offset + 4)? as usize;
+    let header_length = 12 + 16 * table_count;
+
+    let mut result = Vec::with_capacity(collection.len() / 2);
+    result.extend_from_slice(collection.get(font_offset..font_offset + header_length)?);
+    for table in 0..table_count {
+        let record = font_offset + 12 + 16 * table;
+        let offset = read_u32(collection, record + 8)? as usize;
+        let length = read_u32(collection, record + 12)? as usize;
+
+        let new_offset = u32::try_from(result.len()).ok()?;
+        result[12 + 16 * table + 8..12 + 16 * table + 12]
+            .copy_from_slice(&new_offset.to_be_bytes());
+        result.extend_from_slice(collection.get(offset..offset + length)?);
+        // Tables are 4-byte aligned
+        result.resize(result.len().next_multiple_of(4), 0);
+    }
+    Some(result)
+}
+
+/// Loads the export font as a single TrueType font.
+pub fn load_export_font() -> AnyResult<Vec<u8>> {
+    let path = find_font_path().with_context(|| {
+        format!("No export font selected, set {EXPORT_FONT_VARIABLE} in the .env file.")
+    })?;
+    let data =
+        std::fs::read(&path).with_context(|| format!("Failed to read '{}'.", path.display()))?;
+
+    let data = match data.get(..4) {
+        Some(b"ttcf") => extract_first_font(&data)
+            .with_context(|| format!("Font collection '{}' is corrupted.", path.display()))?,
+        Some(b"OTTO") => anyhow::bail!(
+            "Font '{}' has PostScript outlines, only TrueType fonts are supported.",
+            path.display()
+        ),
+        _ => data,
+    };
+
+    ab_glyph::FontRef::try_from_slice(&data)
+        .with_context(|| format!("'{}' is not a valid font.", path.display()))?;
+    Ok(data)
+}
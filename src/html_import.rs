@@ -0,0 +1,101 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+
+use std::path::{Path, PathBuf};
+
+/// Group rebuilt from a file produced by `MangaGroupExporter` with the built-in template.
+#[derive(Debug)]
+pub struct ImportedReview {
+    pub added_on: chrono::NaiveDateTime,
+    pub entries: Vec<ImportedReviewEntry>,
+}
+
+#[derive(Debug)]
+pub struct ImportedReviewEntry {
+    pub name: String,
+    pub score: i64,
+    pub comment: String,
+    /// Absolute paths of the images referenced by the slide.
+    pub images: Vec<PathBuf>,
+}
+
+fn selector(selector: &str) -> scraper::Selector {
+    scraper::Selector::parse(selector).unwrap()
+}
+
+/// Reads `added_on` from the `Manga review #id (added_on)` title, falling back to
+/// the `YYYY-MM-DD` prefix of the exported file name.
+fn parse_added_on(document: &scraper::Html, path: &Path) -> Option<chrono::NaiveDateTime> {
+    let title: String = document
+        .select(&selector("title"))
+        .next()
+        .map(|x| x.text().collect())
+        .unwrap_or_default();
+    let from_title = title
+        .rfind('(')
+        .zip(title.rfind(')'))
+        .and_then(|(start, end)| title.get(start + 1..end))
+        .and_then(|x| chrono::NaiveDateTime::parse_from_str(x, "%Y-%m-%d %H:%M:%S%.f").ok());
+
+    from_title.or_else(|| {
+        let file_name = path.file_name()?.to_string_lossy();
+        chrono::NaiveDate::parse_from_str(file_name.get(..10)?, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)
+    })
+}
+
+/// Image sources are relative to the exported file; exports made on Windows use `\`.
+fn resolve_image_path(export_folder: &Path, src: &str) -> PathBuf {
+    src.split(['/', '\\'])
+        .filter(|x| !x.is_empty())
+        .fold(export_folder.to_path_buf(), |path, component| {
+            path.join(component)
+        })
+}
+
+pub fn read_export_file(path: &Path) -> AnyResult<ImportedReview> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read '{}'.", path.display()))?;
+    let document = scraper::Html::parse_document(&contents);
+    let export_folder = path.parent().unwrap_or(Path::new("."));
+
+    let mut entries = Vec::new();
+    for section in document.select(&selector("section")) {
+        // Sections without a title are the service slides, e.g. "THE END"
+        let Some(title) = section.select(&selector("h3")).next() else {
+            continue;
+        };
+
+        let paragraphs: Vec<String> = section
+            .select(&selector("p"))
+            .map(|x| x.text().collect::<String>().trim().to_string())
+            .collect();
+        let score = paragraphs
+            .first()
+            .and_then(|x| x.strip_suffix("/10"))
+            .and_then(|x| x.trim().parse().ok())
+            .unwrap_or_default();
+
+        entries.push(ImportedReviewEntry {
+            name: title.text().collect::<String>().trim().to_string(),
+            score,
+            comment: paragraphs.get(1).cloned().unwrap_or_default(),
+            images: section
+                .select(&selector("img"))
+                .filter_map(|x| x.value().attr("src"))
+                .map(|src| resolve_image_path(export_folder, src))
+                .collect(),
+        });
+    }
+
+    if entries.is_empty() {
+        anyhow::bail!("No review slides found in '{}'.", path.display());
+    }
+
+    Ok(ImportedReview {
+        added_on: parse_added_on(&document, path)
+            .unwrap_or_else(|| chrono::Local::now().naive_local()),
+        entries,
+    })
+}
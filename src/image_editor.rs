@@ -0,0 +1,190 @@
+use image::RgbaImage;
+
+use shared::types::MangaImage;
+
+/// Longest side of the texture shown in the editor. The image is displayed at most
+/// 800×600 points, so larger textures only cost memory and upload time.
+const MAX_TEXTURE_SIDE: u32 = 2048;
+
+/// What dragging over the image selects an area for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorTool {
+    Crop,
+    BlackBox,
+    Blur,
+}
+
+impl EditorTool {
+    pub const ALL: [Self; 3] = [Self::Crop, Self::BlackBox, Self::Blur];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Crop => "✂ Crop",
+            Self::BlackBox => "⬛ Black box",
+            Self::Blur => "💧 Blur",
+        }
+    }
+}
+
+/// Area of the image in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Selection {
+    /// Area between two corners, clamped to an image of `width`×`height`.
+    pub fn from_corners(a: (f32, f32), b: (f32, f32), width: u32, height: u32) -> Option<Self> {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let clamp = |value: f32, max: u32| value.round().clamp(0., max as f32) as u32;
+        let (left, right) = (clamp(a.0.min(b.0), width), clamp(a.0.max(b.0), width));
+        let (top, bottom) = (clamp(a.1.min(b.1), height), clamp(a.1.max(b.1), height));
+        (right > left && bottom > top).then_some(Self {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        })
+    }
+}
+
+/// Stored image being edited, with every step kept for undo.
+pub struct ImageEditor {
+    pub image: MangaImage,
+    pub history: Vec<RgbaImage>,
+    pub tool: EditorTool,
+    pub selection: Option<Selection>,
+    /// Corner where the current drag started, in image pixels.
+    pub drag_start: Option<(f32, f32)>,
+    pub texture: Option<egui::TextureHandle>,
+}
+
+impl core::fmt::Debug for ImageEditor {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct("ImageEditor")
+            .field("image", &self.image)
+            .field("steps", &self.history.len())
+            .finish()
+    }
+}
+
+impl ImageEditor {
+    pub fn new(image: MangaImage, pixels: RgbaImage) -> Self {
+        Self {
+            image,
+            history: vec![pixels],
+            tool: EditorTool::Crop,
+            selection: None,
+            drag_start: None,
+            texture: None,
+        }
+    }
+
+    pub fn current(&self) -> &RgbaImage {
+        self.history.last().unwrap()
+    }
+
+    fn push(&mut self, image: RgbaImage) {
+        self.history.push(image);
+        self.selection = None;
+        self.texture = None;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.len() > 1
+    }
+
+    pub fn undo(&mut self) {
+        if self.can_undo() {
+            self.history.pop();
+            self.selection = None;
+            self.texture = None;
+        }
+    }
+
+    pub fn rotate_left(&mut self) {
+        self.push(image::imageops::rotate270(self.current()));
+    }
+
+    pub fn rotate_right(&mut self) {
+        self.push(image::imageops::rotate90(self.current()));
+    }
+
+    pub fn flip_horizontally(&mut self) {
+        self.push(image::imageops::flip_horizontal(self.current()));
+    }
+
+    pub fn flip_vertically(&mut self) {
+        self.push(image::imageops::flip_vertical(self.current()));
+    }
+
+    /// Applies the current tool to the selected area.
+    pub fn apply_tool(&mut self) {
+        let Some(area) = self.selection else {
+            return;
+        };
+
+        let image = match self.tool {
+            EditorTool::Crop => {
+                image::imageops::crop_imm(self.current(), area.x, area.y, area.width, area.height)
+                    .to_image()
+            }
+            EditorTool::BlackBox => {
+                let mut image = self.current().clone();
+                let black_box =
+                    RgbaImage::from_pixel(area.width, area.height, image::Rgba([0, 0, 0, 255]));
+                image::imageops::replace(&mut image, &black_box, area.x.into(), area.y.into());
+                image
+            }
+            EditorTool::Blur => {
+                let mut image = self.current().clone();
+                let part = image::imageops::crop_imm(
+                    self.current(),
+                    area.x,
+                    area.y,
+                    area.width,
+                    area.height,
+                )
+                .to_image();
+                // Strong enough for text to be unreadable at any selection size
+                #[allow(clippy::cast_precision_loss)]
+                let sigma = (area.width.min(area.height) as f32 / 8.).max(6.);
+                let blurred = image::imageops::blur(&part, sigma);
+                image::imageops::replace(&mut image, &blurred, area.x.into(), area.y.into());
+                image
+            }
+        };
+        self.push(image);
+    }
+
+    /// Downscaled texture of the current image, recreated after every edit. It's drawn
+    /// over the full image size, so selections still map to full-resolution pixels.
+    pub fn texture(&mut self, ctx: &egui::Context) -> egui::TextureHandle {
+        if self.texture.is_none() {
+            let image = self.current();
+            let preview;
+            let image = if image.width().max(image.height()) > MAX_TEXTURE_SIDE {
+                preview = image::DynamicImage::ImageRgba8(image.clone())
+                    .resize(
+                        MAX_TEXTURE_SIDE,
+                        MAX_TEXTURE_SIDE,
+                        image::imageops::FilterType::Triangle,
+                    )
+                    .to_rgba8();
+                &preview
+            } else {
+                image
+            };
+            let color_image = egui::ColorImage::from_rgba_unmultiplied(
+                [image.width() as usize, image.height() as usize],
+                image.as_raw(),
+            );
+            self.texture =
+                Some(ctx.load_texture("image_editor", color_image, egui::TextureOptions::LINEAR));
+        }
+        self.texture.clone().unwrap()
+    }
+}
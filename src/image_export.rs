@@ -0,0 +1,435 @@
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use shared::types::{MangaEntry, MangaGroup, MangaImage};
+
+const BACKGROUND_COLOR: Rgba<u8> = Rgba([32, 32, 36, 255]);
+const ROW_BACKGROUND_COLOR: Rgba<u8> = Rgba([48, 48, 54, 255]);
+const PLACEHOLDER_COLOR: Rgba<u8> = Rgba([80, 80, 88, 255]);
+const TEXT_COLOR: Rgba<u8> = Rgba([240, 240, 240, 255]);
+const LABEL_TEXT_COLOR: Rgba<u8> = Rgba([20, 20, 20, 255]);
+const PADDING: u32 = 8;
+
+pub const TIER_NAMES: [&str; 5] = ["S", "A", "B", "C", "D"];
+const TIER_COLORS: [Rgba<u8>; 5] = [
+    Rgba([255, 127, 127, 255]),
+    Rgba([255, 191, 127, 255]),
+    Rgba([255, 223, 127, 255]),
+    Rgba([191, 255, 127, 255]),
+    Rgba([127, 191, 255, 255]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageLayout {
+    /// Rows S/A/B/C/D filled by score bands
+    TierList,
+    /// All covers in a plain grid
+    Collage,
+}
+
+impl ImageLayout {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TierList => "Tier list",
+            Self::Collage => "Collage",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageExportOptions {
+    pub layout: ImageLayout,
+    /// Width of the resulting image in pixels, the height depends on the entry count.
+    pub width: u32,
+    /// How many covers fit in one row.
+    pub columns: u32,
+    /// Minimal scores for the S, A, B and C tiers, everything below goes into D.
+    pub tier_thresholds: [i64; 4],
+}
+
+impl Default for ImageExportOptions {
+    fn default() -> Self {
+        Self {
+            layout: ImageLayout::TierList,
+            width: 1600,
+            columns: 8,
+            tier_thresholds: [10, 8, 6, 4],
+        }
+    }
+}
+
+impl ImageExportOptions {
+    pub fn tier_of(&self, score: i64) -> usize {
+        self.tier_thresholds
+            .iter()
+            .position(|threshold| score >= *threshold)
+            .unwrap_or(TIER_NAMES.len() - 1)
+    }
+}
+
+/// Blends `color` over the pixel with the given opacity.
+fn blend_pixel(canvas: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>, coverage: f32) {
+    if x >= canvas.width() || y >= canvas.height() {
+        return;
+    }
+    let coverage = coverage.clamp(0., 1.);
+    let pixel = canvas.get_pixel_mut(x, y);
+    for channel in 0..3 {
+        let blended =
+            f32::from(pixel.0[channel]) * (1. - coverage) + f32::from(color.0[channel]) * coverage;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let blended = blended.round() as u8;
+        pixel.0[channel] = blended;
+    }
+}
+
+fn fill_rectangle(
+    canvas: &mut RgbaImage,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    color: Rgba<u8>,
+) {
+    for pixel_y in y..(y + height).min(canvas.height()) {
+        for pixel_x in x..(x + width).min(canvas.width()) {
+            canvas.put_pixel(pixel_x, pixel_y, color);
+        }
+    }
+}
+
+struct TextRenderer<'a> {
+    font: FontRef<'a>,
+}
+
+impl TextRenderer<'_> {
+    fn text_width(&self, text: &str, size: f32) -> f32 {
+        let font = self.font.as_scaled(PxScale::from(size));
+        text.chars().map(|c| font.h_advance(font.glyph_id(c))).sum()
+    }
+
+    /// Draws a single line of text with its top left corner at `x`, `y`.
+    fn draw_text(
+        &self,
+        canvas: &mut RgbaImage,
+        text: &str,
+        size: f32,
+        x: f32,
+        y: f32,
+        color: Rgba<u8>,
+    ) {
+        let font = self.font.as_scaled(PxScale::from(size));
+        let mut caret = x;
+        for c in text.chars() {
+            let glyph = font
+                .glyph_id(c)
+                .with_scale_and_position(size, ab_glyph::point(caret, y + font.ascent()));
+            caret += font.h_advance(glyph.id);
+            let Some(outline) = self.font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|glyph_x, glyph_y, coverage| {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let (pixel_x, pixel_y) = (
+                    (bounds.min.x + glyph_x as f32) as u32,
+                    (bounds.min.y + glyph_y as f32) as u32,
+                );
+                blend_pixel(canvas, pixel_x, pixel_y, color, coverage);
+            });
+        }
+    }
+
+    /// Breaks text into at most `max_lines` lines fitting into `max_width` pixels,
+    /// cutting it with an ellipsis when it doesn't fit.
+    fn wrap_text(&self, text: &str, size: f32, max_width: f32, max_lines: usize) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::with_capacity(max_lines);
+        let mut line = String::new();
+        for c in text.trim().chars() {
+            line.push(c);
+            if self.text_width(&line, size) <= max_width {
+                continue;
+            }
+            line.pop();
+
+            if lines.len() + 1 == max_lines {
+                while !line.is_empty() && self.text_width(&format!("{line}…"), size) > max_width {
+                    line.pop();
+                }
+                lines.push(format!("{}…", line.trim_end()));
+                return lines;
+            }
+
+            // Prefer breaking at a space, text without spaces is broken anywhere
+            match line.rfind(' ') {
+                Some(position) if c != ' ' && position > 0 => {
+                    let rest = line.split_off(position + 1);
+                    lines.push(line.trim_end().to_string());
+                    line = rest;
+                }
+                _ => lines.push(std::mem::take(&mut line)),
+            }
+            if c != ' ' {
+                line.push(c);
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+pub struct ImageExporter {
+    group: MangaGroup,
+    entries: Vec<(MangaEntry, Vec<MangaImage>)>,
+    options: ImageExportOptions,
+    cwd: std::path::PathBuf,
+}
+
+impl ImageExporter {
+    pub fn new(
+        group: MangaGroup,
+        mut entries: Vec<(MangaEntry, Vec<MangaImage>)>,
+        options: ImageExportOptions,
+    ) -> AnyResult<Self> {
+        crate::export_common::sort_entries(&mut entries);
+        // Best entries go first in both layouts
+        entries.reverse();
+
+        Ok(Self {
+            group,
+            entries,
+            options,
+            cwd: std::env::current_dir().context("Unable to get CWD.")?,
+        })
+    }
+
+    fn title(&self) -> String {
+        format!("Manga review #{} ({})", self.group.id, self.group.added_on)
+    }
+
+    fn label_width(&self) -> u32 {
+        match self.options.layout {
+            ImageLayout::TierList => self.options.width / 12,
+            ImageLayout::Collage => 0,
+        }
+    }
+
+    fn cover_width(&self) -> u32 {
+        let columns = self.options.columns.max(1);
+        let label_width = self.label_width();
+        let available = self
+            .options
+            .width
+            .saturating_sub(label_width + PADDING * (columns + 1));
+        (available / columns).max(16)
+    }
+
+    fn caption_size(&self) -> f32 {
+        #[allow(clippy::cast_precision_loss)]
+        let size = self.cover_width() as f32 / 9.;
+        size.max(10.)
+    }
+
+    fn tile_height(&self) -> u32 {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let caption_height = (self.caption_size() * 1.25 * 2.).ceil() as u32;
+        self.cover_width() * 3 / 2 + caption_height + PADDING
+    }
+
+    /// Rows of tiles needed for this many entries, at least one so that empty tiers are shown.
+    fn rows_for(&self, entry_count: usize) -> u32 {
+        u32::try_from(entry_count)
+            .unwrap_or(u32::MAX)
+            .div_ceil(self.options.columns.max(1))
+            .max(1)
+    }
+
+    fn load_cover(&self, images: &[MangaImage]) -> Option<RgbaImage> {
+        let image = images.first()?;
+        let loaded_image = image::open(self.cwd.join(&image.path)).ok()?;
+        let cover_width = self.cover_width();
+        Some(
+            loaded_image
+                .resize_to_fill(
+                    cover_width,
+                    cover_width * 3 / 2,
+                    image::imageops::FilterType::Lanczos3,
+                )
+                .to_rgba8(),
+        )
+    }
+
+    fn draw_tile(
+        &self,
+        canvas: &mut RgbaImage,
+        text: &TextRenderer,
+        (manga, images): &(MangaEntry, Vec<MangaImage>),
+        x: u32,
+        y: u32,
+    ) {
+        let cover_width = self.cover_width();
+        let cover_height = cover_width * 3 / 2;
+        match self.load_cover(images) {
+            Some(cover) => {
+                image::imageops::overlay(canvas, &cover, i64::from(x), i64::from(y));
+            }
+            None => fill_rectangle(canvas, x, y, cover_width, cover_height, PLACEHOLDER_COLOR),
+        }
+
+        let caption_size = self.caption_size();
+        #[allow(clippy::cast_precision_loss)]
+        let lines = text.wrap_text(&manga.name, caption_size, cover_width as f32, 2);
+        for (index, line) in lines.iter().enumerate() {
+            #[allow(clippy::cast_precision_loss)]
+            let line_y = (y + cover_height) as f32 + 2. + index as f32 * caption_size * 1.25;
+            #[allow(clippy::cast_precision_loss)]
+            text.draw_text(canvas, line, caption_size, x as f32, line_y, TEXT_COLOR);
+        }
+    }
+
+    /// Draws entries in rows starting at `y`, returns the position below the last row.
+    fn draw_tiles(
+        &self,
+        canvas: &mut RgbaImage,
+        text: &TextRenderer,
+        entries: &[&(MangaEntry, Vec<MangaImage>)],
+        left: u32,
+        y: u32,
+    ) -> u32 {
+        let columns = self.options.columns.max(1) as usize;
+        let cover_width = self.cover_width();
+        let tile_height = self.tile_height();
+        for (index, entry) in entries.iter().enumerate() {
+            let (column, row) = (
+                u32::try_from(index % columns).unwrap(),
+                u32::try_from(index / columns).unwrap(),
+            );
+            self.draw_tile(
+                canvas,
+                text,
+                entry,
+                left + PADDING + column * (cover_width + PADDING),
+                y + PADDING + row * tile_height,
+            );
+        }
+        y + PADDING + self.rows_for(entries.len()) * tile_height
+    }
+
+    pub fn render(&self, font_data: &[u8]) -> AnyResult<RgbaImage> {
+        let text = TextRenderer {
+            font: FontRef::try_from_slice(font_data)?,
+        };
+
+        #[allow(clippy::cast_precision_loss)]
+        let title_size = (self.options.width as f32 / 30.).max(16.);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let header_height = (title_size * 1.5).ceil() as u32 + PADDING * 2;
+
+        let tiers: Vec<Vec<&(MangaEntry, Vec<MangaImage>)>> = match self.options.layout {
+            ImageLayout::TierList => {
+                let mut tiers = vec![Vec::new(); TIER_NAMES.len()];
+                for entry in &self.entries {
+                    tiers[self.options.tier_of(entry.0.score)].push(entry);
+                }
+                tiers
+            }
+            ImageLayout::Collage => vec![self.entries.iter().collect()],
+        };
+        let content_height: u32 = tiers
+            .iter()
+            .map(|tier| self.rows_for(tier.len()) * self.tile_height() + PADDING * 2)
+            .sum();
+
+        let mut canvas = RgbaImage::from_pixel(
+            self.options.width,
+            header_height + content_height,
+            BACKGROUND_COLOR,
+        );
+        #[allow(clippy::cast_precision_loss)]
+        text.draw_text(
+            &mut canvas,
+            &self.title(),
+            title_size,
+            PADDING as f32,
+            PADDING as f32,
+            TEXT_COLOR,
+        );
+
+        let label_width = self.label_width();
+        let mut y = header_height;
+        for (tier_index, tier) in tiers.iter().enumerate() {
+            let tier_height = self.rows_for(tier.len()) * self.tile_height() + PADDING;
+            if self.options.layout == ImageLayout::TierList {
+                fill_rectangle(
+                    &mut canvas,
+                    0,
+                    y,
+                    label_width,
+                    tier_height,
+                    TIER_COLORS[tier_index],
+                );
+                fill_rectangle(
+                    &mut canvas,
+                    label_width,
+                    y,
+                    self.options.width - label_width,
+                    tier_height,
+                    ROW_BACKGROUND_COLOR,
+                );
+
+                #[allow(clippy::cast_precision_loss)]
+                let label_size = label_width as f32 / 2.;
+                let label = TIER_NAMES[tier_index];
+                #[allow(clippy::cast_precision_loss)]
+                text.draw_text(
+                    &mut canvas,
+                    label,
+                    label_size,
+                    (label_width as f32 - text.text_width(label, label_size)) / 2.,
+                    y as f32 + (tier_height as f32 - label_size) / 2.,
+                    LABEL_TEXT_COLOR,
+                );
+            }
+
+            self.draw_tiles(&mut canvas, &text, tier, label_width, y);
+            y += tier_height + PADDING;
+        }
+
+        Ok(canvas)
+    }
+
+    pub fn export_group(&self) -> AnyResult<Option<std::path::PathBuf>> {
+        let date = chrono::Local::now().date_naive();
+        let suffix = match self.options.layout {
+            ImageLayout::TierList => "tierlist",
+            ImageLayout::Collage => "collage",
+        };
+
+        let export_path = rfd::FileDialog::new()
+            .set_title("Select image export destination")
+            .set_directory(&self.cwd)
+            .add_filter("PNG image", &["png"])
+            .set_file_name(format!("{}_{}_{suffix}.png", date, self.group.id))
+            .save_file();
+
+        let Some(export_path) = export_path else {
+            return Ok(None);
+        };
+        self.export_to(&export_path)?;
+        Ok(Some(export_path))
+    }
+
+    /// Exports into the given file without asking, e.g. to repeat an earlier export.
+    pub fn export_to(&self, export_path: &std::path::Path) -> AnyResult<()> {
+        let font_data = crate::fonts::load_export_font()?;
+        self.render(&font_data)?
+            .save_with_format(export_path, image::ImageFormat::Png)
+            .with_context(|| format!("Failed to save '{}'.", export_path.display()))?;
+        Ok(())
+    }
+}
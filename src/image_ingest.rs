@@ -0,0 +1,128 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+use serde::{Deserialize, Serialize};
+
+use crate::export_common::{ImageOutputFormat, encode_image};
+
+/// Name of the row in the `settings` table.
+pub const SETTING_NAME: &str = "image_ingest";
+
+/// Formats stored as they are when originals are kept. Browsers can't show the
+/// other ones in exports, so they're converted to PNG.
+const KEPT_FORMATS: [image::ImageFormat; 4] = [
+    image::ImageFormat::Jpeg,
+    image::ImageFormat::Png,
+    image::ImageFormat::Gif,
+    image::ImageFormat::WebP,
+];
+
+/// How images are stored when they're added to an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IngestSettings {
+    /// JPEG, PNG, GIF and WebP files are copied as they are, keeping transparency
+    /// and animation. Other images, including pasted and edited ones, are stored as PNG.
+    pub keep_originals: bool,
+    pub codec: ImageOutputFormat,
+    /// JPEG and WebP quality, 1..=100
+    pub quality: u8,
+    /// Longest side of re-encoded images, 0 keeps their size.
+    pub max_dimension: u32,
+}
+
+impl Default for IngestSettings {
+    fn default() -> Self {
+        Self {
+            keep_originals: false,
+            codec: ImageOutputFormat::Jpeg,
+            quality: 95,
+            max_dimension: 0,
+        }
+    }
+}
+
+/// Image being added, either already decoded or still as file contents.
+#[derive(Debug)]
+pub enum IngestImage {
+    Decoded(image::DynamicImage),
+    File {
+        contents: Vec<u8>,
+        /// Format by the file extension, for formats without a signature like TGA.
+        format: Option<image::ImageFormat>,
+    },
+}
+
+impl IngestImage {
+    pub fn file(contents: Vec<u8>, name: impl AsRef<std::path::Path>) -> Self {
+        Self::File {
+            contents,
+            format: image::ImageFormat::from_path(name).ok(),
+        }
+    }
+}
+
+impl From<image::DynamicImage> for IngestImage {
+    fn from(image: image::DynamicImage) -> Self {
+        Self::Decoded(image)
+    }
+}
+
+/// Whether images of the format can be added. The `image` crate reports AVIF as readable,
+/// but it's built without the `dav1d` decoder, so decoding AVIF always fails.
+pub fn is_readable(format: image::ImageFormat) -> bool {
+    format.reading_enabled() && format != image::ImageFormat::Avif
+}
+
+/// Extensions of every format that can be added, for file dialogs.
+pub fn readable_extensions() -> Vec<&'static str> {
+    image::ImageFormat::all()
+        .filter(|x| is_readable(*x))
+        .flat_map(|x| x.extensions_str().iter().copied())
+        .collect()
+}
+
+impl IngestSettings {
+    /// Contents of the file to store along with its extension.
+    pub fn prepare(&self, image: IngestImage) -> AnyResult<(Vec<u8>, &'static str)> {
+        let image = match image {
+            IngestImage::File { contents, format } => {
+                let format = image::guess_format(&contents)
+                    .ok()
+                    .or(format)
+                    .context("Unknown image format.")?;
+                if !is_readable(format) {
+                    anyhow::bail!(
+                        "{} images aren't supported, convert them to PNG or JPEG first.",
+                        format.extensions_str()[0].to_uppercase()
+                    );
+                }
+                // Decoded even when kept, so broken files don't end up in the library
+                let decoded = image::load_from_memory_with_format(&contents, format)
+                    .context("Failed to decode the image.")?;
+                if self.keep_originals && KEPT_FORMATS.contains(&format) {
+                    return Ok((contents, format.extensions_str()[0]));
+                }
+                decoded
+            }
+            IngestImage::Decoded(image) => image,
+        };
+
+        if self.keep_originals {
+            return Ok((encode_image(&image, ImageOutputFormat::Png, 100)?, "png"));
+        }
+
+        let image =
+            if self.max_dimension > 0 && image.width().max(image.height()) > self.max_dimension {
+                image.resize(
+                    self.max_dimension,
+                    self.max_dimension,
+                    image::imageops::FilterType::Lanczos3,
+                )
+            } else {
+                image
+            };
+        Ok((
+            encode_image(&image, self.codec, self.quality)?,
+            self.codec.extension(),
+        ))
+    }
+}
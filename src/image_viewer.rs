@@ -0,0 +1,106 @@
+use shared::types::{MangaEntry, MangaImage};
+
+/// Longest side of images sent to the viewer, larger ones don't fit into a texture.
+pub const MAX_VIEWER_SIDE: u32 = 4096;
+const ZOOM_RANGE: core::ops::RangeInclusive<f32> = 1.0..=10.0;
+
+/// Full-size view of the images of an entry.
+pub struct ImageViewer {
+    pub entry: MangaEntry,
+    pub images: Vec<MangaImage>,
+    pub index: usize,
+    /// Texture of the current image, missing while it's loading.
+    pub texture: Option<egui::TextureHandle>,
+    /// 1 fits the whole image into the view.
+    pub zoom: f32,
+    /// Offset of the image center from the view center, in points.
+    pub pan: egui::Vec2,
+    pub confirm_delete: bool,
+}
+
+impl core::fmt::Debug for ImageViewer {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct("ImageViewer")
+            .field("entry", &self.entry.id)
+            .field("image", &self.current().id)
+            .finish()
+    }
+}
+
+impl ImageViewer {
+    pub fn new(entry: MangaEntry, images: Vec<MangaImage>, index: usize) -> Self {
+        Self {
+            entry,
+            images,
+            index,
+            texture: None,
+            zoom: 1.,
+            pan: egui::Vec2::ZERO,
+            confirm_delete: false,
+        }
+    }
+
+    pub fn current(&self) -> &MangaImage {
+        &self.images[self.index]
+    }
+
+    fn show(&mut self, index: usize) {
+        self.index = index;
+        self.texture = None;
+        self.confirm_delete = false;
+        self.reset_zoom();
+    }
+
+    pub fn has_previous(&self) -> bool {
+        self.index > 0
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.index + 1 < self.images.len()
+    }
+
+    pub fn show_previous(&mut self) {
+        if self.has_previous() {
+            self.show(self.index - 1);
+        }
+    }
+
+    pub fn show_next(&mut self) {
+        if self.has_next() {
+            self.show(self.index + 1);
+        }
+    }
+
+    /// Drops the current image after it was deleted, returns `false` when none are left.
+    pub fn remove_current(&mut self) -> bool {
+        self.images.remove(self.index);
+        if self.images.is_empty() {
+            return false;
+        }
+        self.show(self.index.min(self.images.len() - 1));
+        true
+    }
+
+    /// Texture is only taken when it's still the current image, the user may have moved on.
+    pub fn set_image(&mut self, ctx: &egui::Context, image_id: i64, image: egui::ColorImage) {
+        if self.current().id == image_id {
+            self.texture =
+                Some(ctx.load_texture("image_viewer", image, egui::TextureOptions::LINEAR));
+        }
+    }
+
+    pub fn reset_zoom(&mut self) {
+        self.zoom = 1.;
+        self.pan = egui::Vec2::ZERO;
+    }
+
+    /// Zooms keeping the point at `anchor`, relative to the view center, in place.
+    pub fn zoom_by(&mut self, factor: f32, anchor: egui::Vec2) {
+        let zoom = (self.zoom * factor).clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+        self.pan = anchor - (anchor - self.pan) * (zoom / self.zoom);
+        self.zoom = zoom;
+        if self.zoom <= *ZOOM_RANGE.start() {
+            self.pan = egui::Vec2::ZERO;
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Name of the row in the `settings` table.
+pub const SETTING_NAME: &str = "inbox";
+/// Subfolder of the inbox that imported files are moved into.
+pub const IMPORTED_FOLDER: &str = "Imported";
+/// How long a file has to stay unchanged before it's picked up,
+/// so screenshots aren't read while they're still being written.
+const SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// What happens to inbox files once they're added to an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AfterImport {
+    Move,
+    Delete,
+}
+
+impl AfterImport {
+    pub const ALL: [Self; 2] = [Self::Move, Self::Delete];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Move => "Move to the \"Imported\" subfolder",
+            Self::Delete => "Delete",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InboxSettings {
+    /// Watched folder, the inbox is off without it.
+    pub folder: Option<PathBuf>,
+    pub after_import: AfterImport,
+}
+
+impl Default for InboxSettings {
+    fn default() -> Self {
+        Self {
+            folder: None,
+            after_import: AfterImport::Move,
+        }
+    }
+}
+
+/// File waiting in the inbox tray to be added to an entry.
+pub struct InboxItem {
+    pub path: PathBuf,
+    pub thumbnail: egui::ImageData,
+}
+
+impl core::fmt::Debug for InboxItem {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct("InboxItem")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+/// Watches the inbox folder, without its subfolders.
+pub struct InboxWatcher {
+    _watcher: notify::RecommendedWatcher,
+    events: crossbeam::channel::Receiver<PathBuf>,
+    /// Changed files along with the time of their last change.
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl InboxWatcher {
+    pub fn new(folder: &Path) -> AnyResult<Self> {
+        let (events_send, events) = crossbeam::channel::unbounded();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event
+                    && (event.kind.is_create() || event.kind.is_modify())
+                {
+                    // Sending only fails while the watcher is being replaced
+                    for path in event.paths {
+                        events_send.send(path).ok();
+                    }
+                }
+            })?;
+        notify::Watcher::watch(&mut watcher, folder, notify::RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch folder '{}'.", folder.display()))?;
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            pending: HashMap::new(),
+        })
+    }
+
+    /// New images that stopped changing since the last call.
+    pub fn settled_files(&mut self) -> Vec<PathBuf> {
+        for path in self.events.try_iter() {
+            self.pending.insert(path, Instant::now());
+        }
+
+        let mut settled = self
+            .pending
+            .iter()
+            .filter(|(_, changed)| changed.elapsed() >= SETTLE_TIME)
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        for path in &settled {
+            self.pending.remove(path);
+        }
+        settled.retain(|x| x.is_file() && crate::data_storage::is_supported_image(x));
+        settled.sort_by(|a, b| natord::compare(&a.to_string_lossy(), &b.to_string_lossy()));
+        settled
+    }
+}
+
+/// Images that were in the folder before it was watched.
+pub fn existing_files(folder: &Path) -> AnyResult<Vec<PathBuf>> {
+    let mut files = std::fs::read_dir(folder)
+        .with_context(|| format!("Failed to read folder '{}'.", folder.display()))?
+        .filter_map(Result::ok)
+        .map(|x| x.path())
+        .filter(|x| x.is_file() && crate::data_storage::is_supported_image(x))
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| natord::compare(&a.to_string_lossy(), &b.to_string_lossy()));
+    Ok(files)
+}
+
+/// Makes tray thumbnails, skipping files that can't be read.
+pub fn load_items(paths: Vec<PathBuf>) -> Vec<InboxItem> {
+    paths
+        .into_par_iter()
+        .filter_map(|path| {
+            let contents = std::fs::read(&path).ok()?;
+            let thumbnail = crate::data_storage::ImageCache::create_thumbnail(&contents).ok()?;
+            Some(InboxItem { path, thumbnail })
+        })
+        .collect()
+}
+
+/// Moves or deletes an imported file.
+pub fn finish_import(path: &Path, after_import: AfterImport) -> AnyResult<()> {
+    match after_import {
+        AfterImport::Move => {
+            let folder = path.parent().unwrap_or(Path::new("")).join(IMPORTED_FOLDER);
+            std::fs::create_dir_all(&folder)
+                .with_context(|| format!("Failed to create '{}'.", folder.display()))?;
+
+            // Files with the same name imported earlier are kept
+            let file_name = path.file_name().unwrap_or_default();
+            let mut destination = folder.join(file_name);
+            let mut counter = 1;
+            while destination.exists() {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                destination = match path.extension() {
+                    Some(extension) => folder.join(format!(
+                        "{stem} ({counter}).{}",
+                        extension.to_string_lossy()
+                    )),
+                    None => folder.join(format!("{stem} ({counter})")),
+                };
+                counter += 1;
+            }
+            std::fs::rename(path, &destination)
+                .with_context(|| format!("Failed to move '{}'.", path.display()))?;
+        }
+        AfterImport::Delete => {
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to delete '{}'.", path.display()))?;
+        }
+    }
+    Ok(())
+}
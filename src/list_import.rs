@@ -0,0 +1,216 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+use serde::Deserialize;
+
+use std::io::Read;
+use std::path::Path;
+
+/// Scale of the scores in an imported list; they're mapped onto our 1..=10 scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListScoreScale {
+    /// MAL and AniList "10 point" formats
+    Ten,
+    /// AniList "100 point" format
+    Hundred,
+    /// AniList "5 star" format
+    Five,
+    /// AniList "3 point smiley" format
+    Three,
+}
+
+impl ListScoreScale {
+    pub const ALL: [Self; 4] = [Self::Ten, Self::Hundred, Self::Five, Self::Three];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ten => "10 points",
+            Self::Hundred => "100 points",
+            Self::Five => "5 stars",
+            Self::Three => "3 smileys",
+        }
+    }
+
+    fn max_score(self) -> f64 {
+        match self {
+            Self::Ten => 10.,
+            Self::Hundred => 100.,
+            Self::Five => 5.,
+            Self::Three => 3.,
+        }
+    }
+
+    /// Maps a list score onto 1..=10, unscored (zero) entries stay at 0.
+    pub fn map_score(self, list_score: f64) -> i64 {
+        if list_score <= 0. {
+            return 0;
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let score = (list_score / self.max_score() * 10.).round() as i64;
+        score.clamp(1, 10)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportedEntry {
+    pub name: String,
+    /// Score as it was in the list, before mapping.
+    pub list_score: f64,
+    pub comment: String,
+    /// Categories from Mihon backups, empty for list exports
+    pub categories: Vec<String>,
+    pub last_read: Option<chrono::NaiveDateTime>,
+    pub selected: bool,
+}
+
+/// Contents of a list export file waiting for the user to confirm the import.
+#[derive(Debug, Clone)]
+pub struct ListImport {
+    pub source: String,
+    pub entries: Vec<ImportedEntry>,
+    pub scale: ListScoreScale,
+}
+
+#[derive(Deserialize)]
+struct MalExport {
+    #[serde(default)]
+    manga: Vec<MalEntry>,
+    #[serde(default)]
+    anime: Vec<MalEntry>,
+}
+
+#[derive(Deserialize)]
+struct MalEntry {
+    #[serde(alias = "series_title")]
+    manga_title: String,
+    #[serde(default)]
+    my_score: Option<f64>,
+    #[serde(default)]
+    my_comments: Option<String>,
+}
+
+/// Reads file contents, unpacking it first if it's gzipped (MAL exports are `.xml.gz`).
+fn read_maybe_gzipped(path: &Path) -> AnyResult<String> {
+    let contents =
+        std::fs::read(path).with_context(|| format!("Failed to read '{}'.", path.display()))?;
+
+    let mut result = String::with_capacity(contents.len() * 4);
+    if contents.starts_with(&[0x1f, 0x8b]) {
+        flate2::read::GzDecoder::new(contents.as_slice())
+            .read_to_string(&mut result)
+            .context("Failed to unpack gzipped file.")?;
+    } else {
+        result = String::from_utf8(contents).context("File is not valid UTF-8.")?;
+    }
+    Ok(result)
+}
+
+fn parse_mal_xml(contents: &str) -> AnyResult<Vec<ImportedEntry>> {
+    let export: MalExport =
+        quick_xml::de::from_str(contents).context("Not a valid MyAnimeList export.")?;
+
+    Ok(export
+        .manga
+        .into_iter()
+        .chain(export.anime)
+        .map(|entry| ImportedEntry {
+            name: entry.manga_title.trim().to_string(),
+            list_score: entry.my_score.unwrap_or_default(),
+            comment: entry.my_comments.unwrap_or_default().trim().to_string(),
+            categories: Vec::new(),
+            last_read: None,
+            selected: true,
+        })
+        .collect())
+}
+
+fn anilist_title(entry: &serde_json::Value) -> Option<String> {
+    let title = entry
+        .get("media")
+        .and_then(|x| x.get("title"))
+        .or_else(|| entry.get("title"))?;
+    if let Some(title) = title.as_str() {
+        return Some(title.to_string());
+    }
+
+    ["userPreferred", "english", "romaji", "native"]
+        .iter()
+        .find_map(|key| title.get(key).and_then(serde_json::Value::as_str))
+        .map(str::to_string)
+}
+
+/// AniList exports come in several shapes (API responses, site exports, plain arrays),
+/// so the whole document is searched for objects that look like list entries.
+fn collect_anilist_entries(value: &serde_json::Value, result: &mut Vec<ImportedEntry>) {
+    match value {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_anilist_entries(item, result);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            if map.contains_key("score")
+                && let Some(name) = anilist_title(value)
+            {
+                result.push(ImportedEntry {
+                    name: name.trim().to_string(),
+                    list_score: map["score"].as_f64().unwrap_or_default(),
+                    comment: map
+                        .get("notes")
+                        .and_then(serde_json::Value::as_str)
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string(),
+                    categories: Vec::new(),
+                    last_read: None,
+                    selected: true,
+                });
+                return;
+            }
+            for item in map.values() {
+                collect_anilist_entries(item, result);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn parse_anilist_json(contents: &str) -> AnyResult<Vec<ImportedEntry>> {
+    let document: serde_json::Value =
+        serde_json::from_str(contents).context("Not a valid JSON file.")?;
+    let mut result = Vec::new();
+    collect_anilist_entries(&document, &mut result);
+    Ok(result)
+}
+
+/// Reads a MyAnimeList XML (optionally gzipped) or AniList JSON export file.
+pub fn read_list_file(path: &Path) -> AnyResult<ListImport> {
+    let contents = read_maybe_gzipped(path)?;
+    let is_xml = contents.trim_start().starts_with('<');
+
+    let entries = if is_xml {
+        parse_mal_xml(&contents)?
+    } else {
+        parse_anilist_json(&contents)?
+    };
+    if entries.is_empty() {
+        anyhow::bail!("No list entries found in '{}'.", path.display());
+    }
+
+    let max_score = entries.iter().map(|x| x.list_score).fold(0., f64::max);
+    let scale = if !is_xml && max_score > 10. {
+        ListScoreScale::Hundred
+    } else {
+        ListScoreScale::Ten
+    };
+
+    Ok(ListImport {
+        source: path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+        entries,
+        scale,
+    })
+}
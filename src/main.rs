@@ -7,10 +7,34 @@ use eframe::egui::Vec2 as EguiVec2;
 use data_storage::DataStorage;
 use manga_ui::{MangaUI, UiMessenger};
 
+mod archive_import;
 mod cascade_delete;
+mod clipboard;
+mod csv_transfer;
 mod data_storage;
+mod epub_export;
+mod export_common;
+mod export_helpers;
+mod export_lint;
+mod export_preview;
+mod folder_names;
+mod folder_sync;
+mod fonts;
+mod html_import;
+mod image_editor;
+mod image_export;
+mod image_ingest;
+mod image_viewer;
+mod inbox;
+mod list_import;
 mod manga_group_export;
 mod manga_ui;
+mod markdown_export;
+mod mihon_import;
+mod pdf_export;
+mod site_export;
+mod source_import;
+mod text_export;
 mod types;
 
 fn main() -> AnyResult<()> {
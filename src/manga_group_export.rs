@@ -1,146 +1,325 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+use serde::Serialize;
+
+use crate::export_common::ExportImageOptions;
 use shared::types::{MangaEntry, MangaGroup, MangaImage};
 
 static TEMPLATE: &str = include_str!("template.html");
 
 static SECTION_ELEMENT: &str = r#"
 <section data-transition-speed="fast">
-    <h3>{{title}}</h3>
+    <h3>{{name}}</h3>
     <p>{{score}}/10</p>
     <p>{{comment}}</p>
-    {{image_counter}}
-    {{image_stack}}
+    {{#if (gt image_count 1)}}<div style="font-size:0.3em">{{pluralize image_count "image"}}</div>{{/if}}
+    {{> images}}
 </section>
 "#;
 
 static IMAGE_ELEMENT: &str = r#"
+{{#if (eq image_count 1)}}
+    <div class="r-stretch"><img src="{{images.0.src}}"></div>
+{{else if (gt image_count 1)}}
     <div class="r-stack r-stretch">
-        {{image_elements}}
+        {{#each images}}
+        {{#if @first}}
+        <img class="fragment fade-out" data-fragment-index="0" src="{{src}}">
+        {{else if (eq @index 1)}}
+        <img class="fragment fade-in-then-out" data-fragment-index="0" src="{{src}}">
+        {{else}}
+        <img class="fragment fade-in-then-out" src="{{src}}">
+        {{/if}}
+        {{/each}}
     </div>
+{{/if}}
 "#;
 
+/// Name under which the compiled-in reveal.js template is offered.
+pub const BUILTIN_TEMPLATE_NAME: &str = "reveal.js (built-in)";
+
+/// Folder (relative to CWD) that user templates are loaded from.
+///
+/// Every `*.hbs` file in it is offered as a template; `name.html.hbs` produces
+/// `.html` files, a plain `name.hbs` defaults to `.html` as well.
+/// Files in its `partials` sub-folder are registered as partials under their
+/// file stem and override the built-in `section` and `images` partials.
+pub const TEMPLATES_FOLDER: &str = "templates";
+
+/// Root object passed to export templates.
+#[derive(Debug, Serialize)]
+pub struct ExportData {
+    /// Ready-made document title, e.g. `Manga review #3 (2024-05-01 18:00:00)`.
+    pub title: String,
+    pub group: ExportGroupData,
+    /// Entries sorted by score, then by name.
+    pub entries: Vec<ExportEntryData>,
+    pub scores: ExportScoresData,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportGroupData {
+    pub id: i64,
+    /// Date the group was created, as `YYYY-MM-DD HH:MM:SS`.
+    pub added_on: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportEntryData {
+    pub id: i64,
+    pub name: String,
+    pub score: i64,
+    pub comment: String,
+    /// Number of items in `images`.
+    pub image_count: usize,
+    pub images: Vec<ExportImageData>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportImageData {
+    /// Path of the copied image, relative to the exported file.
+    pub src: String,
+    /// Zero-based position of the image inside its entry.
+    pub index: usize,
+}
+
+/// Score statistics over all entries of the group; all zeroes for an empty group.
+#[derive(Debug, Serialize)]
+pub struct ExportScoresData {
+    pub min: i64,
+    pub max: i64,
+    pub average: f64,
+}
+
+/// Returns names of all available export templates, built-in one first.
+pub fn list_templates(cwd: &std::path::Path) -> Vec<String> {
+    let mut result = vec![BUILTIN_TEMPLATE_NAME.to_string()];
+
+    let Ok(contents) = std::fs::read_dir(cwd.join(TEMPLATES_FOLDER)) else {
+        return result;
+    };
+    let mut user_templates: Vec<String> = contents
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "hbs"))
+        .filter_map(|path| path.file_name().map(|x| x.to_string_lossy().into_owned()))
+        .collect();
+    user_templates.sort();
+
+    result.extend(user_templates);
+    result
+}
+
 pub struct MangaGroupExporter<'a> {
     group: MangaGroup,
     entries: Vec<(MangaEntry, Vec<MangaImage>)>,
     handlebars: handlebars::Handlebars<'a>,
+    template_name: String,
+    image_options: ExportImageOptions,
     cwd: std::path::PathBuf,
     export_path: std::path::PathBuf,
 }
 
 impl<'a> MangaGroupExporter<'a> {
-    pub fn new(group: MangaGroup, mut entries: Vec<(MangaEntry, Vec<MangaImage>)>) -> Self {
+    pub fn new(
+        group: MangaGroup,
+        mut entries: Vec<(MangaEntry, Vec<MangaImage>)>,
+        template_name: &str,
+        image_options: ExportImageOptions,
+    ) -> AnyResult<Self> {
+        let cwd = std::env::current_dir().context("Unable to get CWD.")?;
+
         let mut handlebars = handlebars::Handlebars::new();
         handlebars.set_strict_mode(true);
         handlebars.register_escape_fn(handlebars::no_escape);
-        handlebars
-            .register_template_string("main_template", TEMPLATE)
-            .unwrap();
-        handlebars
-            .register_template_string("section_template", SECTION_ELEMENT)
-            .unwrap();
-        handlebars
-            .register_template_string("image_template", IMAGE_ELEMENT)
-            .unwrap();
-
-        entries.sort_by(|a, b| a.0.name.cmp(&b.0.name));
-        entries.sort_by(|a, b| a.0.score.cmp(&b.0.score));
-
-        Self {
+        crate::export_helpers::register_helpers(&mut handlebars, &cwd)?;
+        handlebars.register_partial("section", SECTION_ELEMENT)?;
+        handlebars.register_partial("images", IMAGE_ELEMENT)?;
+        Self::register_user_partials(&mut handlebars, &cwd)?;
+
+        if template_name == BUILTIN_TEMPLATE_NAME {
+            handlebars.register_template_string("main_template", TEMPLATE)?;
+        } else {
+            let template_path = cwd.join(TEMPLATES_FOLDER).join(template_name);
+            let template = std::fs::read_to_string(&template_path).with_context(|| {
+                format!("Failed to read template '{}'.", template_path.display())
+            })?;
+            handlebars
+                .register_template_string("main_template", template)
+                .with_context(|| format!("Template '{template_name}' is invalid."))?;
+        }
+
+        crate::export_common::sort_entries(&mut entries);
+
+        Ok(Self {
             group,
             entries,
             handlebars,
-            cwd: std::env::current_dir().unwrap(),
-            export_path: std::env::current_dir().unwrap(),
+            template_name: template_name.to_string(),
+            image_options,
+            cwd: cwd.clone(),
+            export_path: cwd,
+        })
+    }
+
+    fn register_user_partials(
+        handlebars: &mut handlebars::Handlebars,
+        cwd: &std::path::Path,
+    ) -> AnyResult<()> {
+        let Ok(contents) = std::fs::read_dir(cwd.join(TEMPLATES_FOLDER).join("partials")) else {
+            return Ok(());
+        };
+
+        for entry in contents.filter_map(Result::ok) {
+            let path = entry.path();
+            if !path.is_file() || path.extension().is_none_or(|ext| ext != "hbs") {
+                continue;
+            }
+            let name = path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            let partial = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read partial '{}'.", path.display()))?;
+            handlebars
+                .register_partial(&name, partial)
+                .with_context(|| format!("Partial '{name}' is invalid."))?;
         }
+
+        Ok(())
     }
 
-    fn _copy_image(&self, image: &MangaImage) -> String {
-        let full_path_from = self.cwd.join(&image.path);
-        let relative_folder_to = std::path::PathBuf::new()
-            .join("media")
-            .join(format!("review_{}", self.group.id));
-        let full_folder_to = self.export_path.parent().unwrap().join(&relative_folder_to);
-        if !full_folder_to.exists() {
-            std::fs::create_dir_all(&full_folder_to).unwrap();
+    /// Extension of the files produced by the selected template.
+    fn output_extension(&self) -> String {
+        if self.template_name == BUILTIN_TEMPLATE_NAME {
+            return "html".to_string();
         }
 
-        let filename = full_path_from.file_name().unwrap().to_string_lossy();
-        let full_path_to = full_folder_to.join(&*filename);
-        std::fs::copy(&full_path_from, full_path_to).unwrap();
-        relative_folder_to
-            .join(&*filename)
-            .to_string_lossy()
-            .into_owned()
+        let stem = std::path::Path::new(&self.template_name).with_extension("");
+        stem.extension()
+            .map_or_else(|| "html".to_string(), |x| x.to_string_lossy().into_owned())
     }
 
-    fn _create_image_element(&self, images: &[MangaImage]) -> String {
-        match images.len() {
-            0 => String::new(),
-            1 => format!(
-                r#"<div class="r-stretch"><img src="{}"></div>"#,
-                self._copy_image(&images[0])
-            ),
-            _ => {
-                let mut elements = Vec::with_capacity(images.len());
-                for (index, elem) in images.iter().enumerate() {
-                    match index {
-                        0 => elements.push(format!(r#"<img class="fragment fade-out" data-fragment-index="0" src="{}">"#, self._copy_image(elem))),
-                        1 => elements.push(format!(r#"<img class="fragment fade-in-then-out" data-fragment-index="0" src="{}">"#, self._copy_image(elem))),
-                        _ => elements.push(format!(r#"<img class="fragment fade-in-then-out" src="{}">"#, self._copy_image(elem))),
-                    }
-                }
-                let mut data = std::collections::HashMap::new();
-                data.insert("image_elements", elements.join("\n"));
-
-                self.handlebars.render("image_template", &data).unwrap()
-            }
+    fn _create_entry_data(
+        manga: &MangaEntry,
+        images: &[MangaImage],
+        image_paths: &std::collections::HashMap<i64, String>,
+    ) -> ExportEntryData {
+        let image_data: Vec<ExportImageData> = images
+            .iter()
+            .enumerate()
+            .map(|(index, image)| ExportImageData {
+                src: image_paths[&image.id].clone(),
+                index,
+            })
+            .collect();
+
+        ExportEntryData {
+            id: manga.id,
+            name: manga.name.clone(),
+            score: manga.score,
+            comment: manga.comment.clone(),
+            image_count: image_data.len(),
+            images: image_data,
         }
     }
 
-    fn _create_manga_element(&self, manga: &MangaEntry, images: &[MangaImage]) -> String {
-        let image_element = self._create_image_element(images);
-        let mut data = std::collections::HashMap::new();
-        data.insert("title", manga.name.clone());
-        data.insert("score", manga.score.to_string());
-        data.insert("comment", manga.comment.clone());
-        let images_count = if images.len() > 1 {
-            format!(r#"<div style="font-size:0.3em">{} images</div>"#, images.len())
-        } else {
-            String::new()
-        };
-        data.insert("image_counter", images_count);
-        data.insert("image_stack", image_element);
-        self.handlebars.render("section_template", &data).unwrap()
+    fn _create_scores_data(&self) -> ExportScoresData {
+        let scores: Vec<i64> = self.entries.iter().map(|(entry, _)| entry.score).collect();
+        if scores.is_empty() {
+            return ExportScoresData {
+                min: 0,
+                max: 0,
+                average: 0.,
+            };
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let average = scores.iter().sum::<i64>() as f64 / scores.len() as f64;
+        ExportScoresData {
+            min: *scores.iter().min().unwrap(),
+            max: *scores.iter().max().unwrap(),
+            average,
+        }
     }
 
-    pub fn export_group(&mut self) {
+    /// Asks for the destination and exports there, returns the chosen path.
+    pub fn export_group(&mut self) -> AnyResult<Option<std::path::PathBuf>> {
         let date = chrono::Local::now().date_naive();
+        let extension = self.output_extension();
 
         let export_filepath = rfd::FileDialog::new()
             .set_title("Select export destination")
-            .set_directory(std::env::current_dir().unwrap())
-            .add_filter("HTML file", &["html"])
-            .set_file_name(format!("{}_{}.html", date, self.group.id))
+            .set_directory(&self.cwd)
+            .add_filter(format!("{} file", extension.to_uppercase()), &[&extension])
+            .set_file_name(format!("{}_{}.{}", date, self.group.id, extension))
             .save_file();
 
-        if export_filepath.is_none() {
-            return;
-        }
+        let Some(export_filepath) = export_filepath else {
+            return Ok(None);
+        };
+        self.export_to(&export_filepath)?;
+        Ok(Some(export_filepath))
+    }
 
-        self.export_path = export_filepath.unwrap();
+    /// Exports into the given file without asking, e.g. to repeat an earlier export.
+    pub fn export_to(&mut self, export_filepath: &std::path::Path) -> AnyResult<()> {
+        self.export_path = export_filepath.to_path_buf();
 
-        let mut elements = Vec::with_capacity(self.entries.len());
-        for (manga, images) in &self.entries {
-            elements.push(self._create_manga_element(manga, images));
-        }
+        let image_paths = crate::export_common::copy_group_images(
+            &self.cwd,
+            self.export_path
+                .parent()
+                .context("Export path has no parent folder.")?,
+            self.group.id,
+            &self.entries,
+            &self.image_options,
+        )?;
+        let result = self.render(&image_paths)?;
 
-        let mut data = std::collections::HashMap::new();
-        data.insert(
-            "title",
-            format!("Manga review #{} ({})", self.group.id, self.group.added_on),
-        );
-        data.insert("sections", elements.join("\n"));
-        let result = self.handlebars.render("main_template", &data).unwrap();
+        std::fs::write(&self.export_path, result)
+            .with_context(|| format!("Failed to write '{}'.", self.export_path.display()))?;
+        Ok(())
+    }
 
-        std::fs::write(&self.export_path, result).unwrap();
+    /// Renders the slideshow with the stored images in place of the exported ones.
+    pub fn preview(&self) -> AnyResult<crate::export_preview::ExportPreview> {
+        let image_paths = self
+            .entries
+            .iter()
+            .flat_map(|(_, images)| images)
+            .map(|image| (image.id, image.path.clone()))
+            .collect();
+        let html = self.render(&image_paths)?;
+        Ok(crate::export_preview::build_preview(
+            &self.template_name,
+            &html,
+        ))
+    }
+
+    /// Renders the selected template with images at the given (already copied) paths.
+    pub fn render(
+        &self,
+        image_paths: &std::collections::HashMap<i64, String>,
+    ) -> AnyResult<String> {
+        let entries = self
+            .entries
+            .iter()
+            .map(|(manga, images)| Self::_create_entry_data(manga, images, image_paths))
+            .collect();
+
+        let data = ExportData {
+            title: format!("Manga review #{} ({})", self.group.id, self.group.added_on),
+            group: ExportGroupData {
+                id: self.group.id,
+                added_on: self.group.added_on.to_string(),
+            },
+            entries,
+            scores: self._create_scores_data(),
+        };
+        self.handlebars
+            .render("main_template", &data)
+            .with_context(|| format!("Failed to render template '{}'.", self.template_name))
     }
 }
@@ -7,8 +7,27 @@ use anyhow::Context;
 use anyhow::Result as AnyResult;
 use eframe::egui::{Color32, Stroke, Vec2 as EguiVec2};
 
-use crate::types::{BackendChannelRecv, BackendCommand, GuiChannelSend, GuiCommand, SqlitePool};
-use shared::types::{DisplayedMangaEntry, MangaEntry, MangaGroup, MangaImage};
+use crate::archive_import::ArchivePages;
+use crate::clipboard::Clipboard;
+use crate::csv_transfer::CsvImportChange;
+use crate::export_common::{ExportImageOptions, ImageOutputFormat, ImagePreset};
+use crate::export_lint::{LintAction, LintReport};
+use crate::export_preview::ExportPreview;
+use crate::folder_names::{FolderNameReview, NameCleanupRules};
+use crate::folder_sync::{FolderSyncPlan, SyncChange, VanishedAction};
+use crate::image_editor::{EditorTool, ImageEditor, Selection};
+use crate::image_export::{ImageExportOptions, ImageLayout, TIER_NAMES};
+use crate::image_ingest::IngestSettings;
+use crate::image_viewer::ImageViewer;
+use crate::inbox::{AfterImport, InboxSettings};
+use crate::list_import::{ListImport, ListScoreScale};
+use crate::site_export::SiteLayout;
+use crate::source_import::{PageSampling, SourceImportOptions};
+use crate::text_export::TextExportFormat;
+use crate::types::{
+    BackendChannelRecv, BackendCommand, GuiChannelSend, GuiCommand, SCORE_RANGE, SqlitePool,
+};
+use shared::types::{DisplayedMangaEntry, MangaEntry, MangaExport, MangaGroup, MangaImage};
 
 pub struct UiMessenger {
     pub backend_recv: BackendChannelRecv,
@@ -50,6 +69,67 @@ impl UiMessenger {
             .unwrap();
     }
 
+    fn add_dropped_images(&self, entry: &MangaEntry, paths: Vec<std::path::PathBuf>) {
+        self.gui_send
+            .send(GuiCommand::AddImagesFromPaths(entry.clone(), paths))
+            .unwrap();
+        self.gui_send
+            .send(GuiCommand::UpdateEntryImages(entry.clone()))
+            .unwrap();
+    }
+
+    fn open_archive(&self, entry: &MangaEntry) {
+        self.gui_send
+            .send(GuiCommand::OpenArchive(entry.clone()))
+            .unwrap();
+    }
+
+    fn add_archive_pages(&self, pages: &ArchivePages, names: Vec<String>) {
+        self.gui_send
+            .send(GuiCommand::AddImagesFromArchive(
+                pages.entry.clone(),
+                pages.path.clone(),
+                names,
+            ))
+            .unwrap();
+        self.gui_send
+            .send(GuiCommand::UpdateEntryImages(pages.entry.clone()))
+            .unwrap();
+    }
+
+    fn repick_source_pages(&self, entry: &MangaEntry, options: SourceImportOptions) {
+        self.gui_send
+            .send(GuiCommand::RepickSourcePages(entry.clone(), options))
+            .unwrap();
+        self.gui_send
+            .send(GuiCommand::UpdateEntryImages(entry.clone()))
+            .unwrap();
+    }
+
+    fn set_inbox_target(&self, entry: Option<&MangaEntry>) {
+        self.gui_send
+            .send(GuiCommand::SetInboxTarget(entry.cloned()))
+            .unwrap();
+    }
+
+    fn add_inbox_files(&self, entry: &MangaEntry, paths: Vec<std::path::PathBuf>) {
+        self.gui_send
+            .send(GuiCommand::AddInboxFiles(entry.clone(), paths))
+            .unwrap();
+    }
+
+    fn load_full_image(&self, image: &MangaImage) {
+        self.gui_send
+            .send(GuiCommand::LoadFullImage(image.clone()))
+            .unwrap();
+    }
+
+    fn open_image_editor(&self, image: &MangaImage) {
+        self.gui_send
+            .send(GuiCommand::OpenImageEditor(image.clone()))
+            .unwrap();
+    }
+
     fn add_image_from_clipboard(&self, entry: &MangaEntry) {
         self.gui_send
             .send(GuiCommand::AddImageFromClipboard(entry.clone()))
@@ -74,6 +154,20 @@ impl MangaWebServer {
     }
 }
 
+/// Pages of an opened archive with their selection.
+pub struct ArchivePicker {
+    pub pages: ArchivePages,
+    pub textures: Vec<egui::TextureHandle>,
+    pub selected: Vec<bool>,
+}
+
+/// File waiting in the inbox tray.
+pub struct InboxTrayItem {
+    pub path: std::path::PathBuf,
+    pub texture: egui::TextureHandle,
+    pub selected: bool,
+}
+
 pub struct MangaUI {
     pub manga_groups: Vec<MangaGroup>,
     pub selected_group: Option<MangaGroup>,
@@ -81,6 +175,49 @@ pub struct MangaUI {
     pub entry_to_delete: Option<MangaEntry>,
     pub manga_entries: Option<Vec<DisplayedMangaEntry>>,
     pub messenger: UiMessenger,
+    pub export_templates: Vec<String>,
+    pub selected_export_template: String,
+    pub markdown_front_matter: bool,
+    pub export_image_options: ExportImageOptions,
+    pub export_preview: Option<ExportPreview>,
+    pub export_history: Vec<MangaExport>,
+    pub show_export_history: bool,
+    pub text_export: Option<(TextExportFormat, Vec<String>)>,
+    pub copied_text_messages: Vec<bool>,
+    pub clipboard: Option<Clipboard>,
+    pub csv_import_preview: Option<Vec<CsvImportChange>>,
+    pub list_import: Option<ListImport>,
+    pub list_import_into_new_group: bool,
+    pub image_export_options: ImageExportOptions,
+    pub show_image_export: bool,
+    pub epub_group_selection: Option<Vec<(MangaGroup, bool)>>,
+    pub lint_report: Option<LintReport>,
+    pub archive_picker: Option<ArchivePicker>,
+    pub source_import_options: SourceImportOptions,
+    pub show_source_import: bool,
+    pub name_cleanup_rules: NameCleanupRules,
+    /// Name shown cleaned up by the current rules, to try them out.
+    pub name_cleanup_sample: String,
+    pub show_name_cleanup_rules: bool,
+    /// Whether the folder names are added from becomes the source folder of the group.
+    pub remember_names_folder: bool,
+    pub folder_sync: Option<FolderSyncPlan>,
+    pub image_editor: Option<ImageEditor>,
+    pub image_viewer: Option<ImageViewer>,
+    pub image_to_delete: Option<(MangaImage, MangaEntry)>,
+    pub ingest_settings: IngestSettings,
+    /// Copy of the image storage settings while their window is open.
+    pub ingest_settings_edit: Option<IngestSettings>,
+    pub inbox_settings: InboxSettings,
+    /// Copy of the inbox settings while their window is open.
+    pub inbox_settings_edit: Option<InboxSettings>,
+    pub inbox_items: Vec<InboxTrayItem>,
+    pub inbox_target: Option<MangaEntry>,
+    pub folder_name_review: Option<FolderNameReview>,
+    /// Entry picked from the lint report, drawn with a red frame.
+    pub highlighted_entry: Option<i64>,
+    scroll_to_highlighted_entry: bool,
+    pub error_message: Option<String>,
     loading: bool,
     web_server: MangaWebServer,
 }
@@ -94,6 +231,44 @@ impl MangaUI {
             entry_to_delete: Option::None,
             manga_entries: Option::None,
             messenger,
+            export_templates: Vec::new(),
+            selected_export_template: crate::manga_group_export::BUILTIN_TEMPLATE_NAME.to_string(),
+            markdown_front_matter: true,
+            export_image_options: ExportImageOptions::default(),
+            export_preview: Option::None,
+            export_history: Vec::new(),
+            show_export_history: false,
+            text_export: Option::None,
+            copied_text_messages: Vec::new(),
+            clipboard: Option::None,
+            csv_import_preview: Option::None,
+            list_import: Option::None,
+            list_import_into_new_group: true,
+            image_export_options: ImageExportOptions::default(),
+            show_image_export: false,
+            epub_group_selection: Option::None,
+            lint_report: Option::None,
+            archive_picker: Option::None,
+            source_import_options: SourceImportOptions::default(),
+            show_source_import: false,
+            name_cleanup_rules: NameCleanupRules::default(),
+            name_cleanup_sample: "[Group] Some Title v01 (2019) (Digital)".to_string(),
+            show_name_cleanup_rules: false,
+            remember_names_folder: true,
+            folder_sync: Option::None,
+            image_editor: Option::None,
+            image_viewer: Option::None,
+            image_to_delete: Option::None,
+            ingest_settings: IngestSettings::default(),
+            ingest_settings_edit: Option::None,
+            inbox_settings: InboxSettings::default(),
+            inbox_settings_edit: Option::None,
+            inbox_items: Vec::new(),
+            inbox_target: Option::None,
+            folder_name_review: Option::None,
+            highlighted_entry: Option::None,
+            scroll_to_highlighted_entry: false,
+            error_message: Option::None,
             loading: false,
             web_server: MangaWebServer::new(),
         }
@@ -114,6 +289,12 @@ impl eframe::App for MangaUI {
                 self.draw_manga_groups_panel(ctx, ui);
             });
 
+        if self.inbox_settings.folder.is_some() {
+            egui::TopBottomPanel::bottom("bottom_panel_inbox").show(ctx, |ui| {
+                self.draw_inbox_tray(ui);
+            });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             self.draw_central_manga_entries_panel(ctx, ui);
         });
@@ -122,6 +303,82 @@ impl eframe::App for MangaUI {
             self.draw_group_delete_confirm(ctx);
         }
 
+        if self.text_export.is_some() {
+            self.draw_text_export(ctx);
+        }
+
+        if self.csv_import_preview.is_some() {
+            self.draw_csv_import_preview(ctx);
+        }
+
+        if self.list_import.is_some() {
+            self.draw_list_import_preview(ctx);
+        }
+
+        if self.export_preview.is_some() {
+            self.draw_export_preview(ctx);
+        }
+
+        if self.show_export_history {
+            self.draw_export_history(ctx);
+        }
+
+        if self.epub_group_selection.is_some() {
+            self.draw_epub_group_selection(ctx);
+        }
+
+        if self.show_image_export {
+            self.draw_image_export(ctx);
+        }
+
+        if self.lint_report.is_some() {
+            self.draw_lint_report(ctx);
+        }
+
+        if self.archive_picker.is_some() {
+            self.draw_archive_picker(ctx);
+        }
+
+        if self.show_source_import {
+            self.draw_source_import(ctx);
+        }
+
+        if self.ingest_settings_edit.is_some() {
+            self.draw_ingest_settings(ctx);
+        }
+
+        if self.inbox_settings_edit.is_some() {
+            self.draw_inbox_settings(ctx);
+        }
+
+        if self.show_name_cleanup_rules {
+            self.draw_name_cleanup_rules(ctx);
+        }
+
+        if self.folder_name_review.is_some() {
+            self.draw_folder_name_review(ctx);
+        }
+
+        if self.folder_sync.is_some() {
+            self.draw_folder_sync(ctx);
+        }
+
+        if self.image_viewer.is_some() {
+            self.draw_image_viewer(ctx);
+        }
+
+        if self.image_to_delete.is_some() {
+            self.draw_image_delete_confirm(ctx);
+        }
+
+        if self.image_editor.is_some() {
+            self.draw_image_editor(ctx);
+        }
+
+        if self.error_message.is_some() {
+            self.draw_error_message(ctx);
+        }
+
         #[cfg(debug_assertions)]
         {
             ctx.set_debug_on_hover(true);
@@ -184,19 +441,242 @@ impl MangaUI {
     }
 
     fn export_group(&mut self) {
+        self.lint_group(LintAction::Export);
+    }
+
+    fn lint_group(&mut self, action: LintAction) {
+        let Some(entries) = &self.manga_entries else {
+            return;
+        };
+        self.messenger
+            .gui_send
+            .send(GuiCommand::LintGroup(
+                self.selected_group.as_ref().unwrap().clone(),
+                entries.iter().map(|x| x.entry.clone()).collect(),
+                action,
+            ))
+            .unwrap();
+    }
+
+    fn run_lint_action(&mut self, action: LintAction) {
+        match action {
+            LintAction::Export => self.send_export_group(),
+            LintAction::WebShare => {
+                if self.web_server.handle.is_none() && self.manga_entries.is_some() {
+                    self.start_web_server();
+                }
+            }
+        }
+    }
+
+    fn send_export_group(&mut self) {
         self.messenger
             .gui_send
             .send(GuiCommand::ExportGroup(
                 self.selected_group.as_ref().unwrap().clone(),
+                self.selected_export_template.clone(),
+                self.export_image_options,
+            ))
+            .unwrap();
+    }
+
+    fn preview_export(&mut self) {
+        self.messenger
+            .gui_send
+            .send(GuiCommand::PreviewExport(
+                self.selected_group.as_ref().unwrap().clone(),
+                self.selected_export_template.clone(),
+                self.export_image_options,
+            ))
+            .unwrap();
+    }
+
+    fn export_group_markdown(&mut self) {
+        self.messenger
+            .gui_send
+            .send(GuiCommand::ExportGroupMarkdown(
+                self.selected_group.as_ref().unwrap().clone(),
+                self.markdown_front_matter,
+                self.export_image_options,
+            ))
+            .unwrap();
+    }
+
+    fn export_group_pdf(&mut self) {
+        self.messenger
+            .gui_send
+            .send(GuiCommand::ExportGroupPdf(
+                self.selected_group.as_ref().unwrap().clone(),
+            ))
+            .unwrap();
+    }
+
+    fn export_epub(&mut self, groups: Vec<MangaGroup>) {
+        self.messenger
+            .gui_send
+            .send(GuiCommand::ExportEpub(groups))
+            .unwrap();
+    }
+
+    fn export_group_image(&mut self) {
+        self.messenger
+            .gui_send
+            .send(GuiCommand::ExportGroupImage(
+                self.selected_group.as_ref().unwrap().clone(),
+                self.image_export_options.clone(),
+            ))
+            .unwrap();
+    }
+
+    fn export_site(&mut self, layout: SiteLayout) {
+        self.messenger
+            .gui_send
+            .send(GuiCommand::ExportSite(
+                layout,
+                self.selected_export_template.clone(),
+                self.export_image_options,
+            ))
+            .unwrap();
+    }
+
+    fn export_group_text(&mut self, format: TextExportFormat) {
+        self.messenger
+            .gui_send
+            .send(GuiCommand::ExportGroupText(
+                self.selected_group.as_ref().unwrap().clone(),
+                format,
             ))
             .unwrap();
     }
 
+    fn copy_text_message(&mut self, index: usize) {
+        let Some((_, messages)) = &self.text_export else {
+            return;
+        };
+
+        if self.clipboard.is_none() {
+            match Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(error) => {
+                    self.error_message = Some(format!("{error:#}"));
+                    return;
+                }
+            }
+        }
+
+        match self.clipboard.as_mut().unwrap().set_text(&messages[index]) {
+            Ok(()) => self.copied_text_messages[index] = true,
+            Err(error) => self.error_message = Some(format!("{error:#}")),
+        }
+    }
+
+    fn export_entries_csv(&mut self, whole_library: bool) {
+        let group = if whole_library {
+            None
+        } else {
+            self.selected_group.clone()
+        };
+        self.messenger
+            .gui_send
+            .send(GuiCommand::ExportEntriesCsv(group))
+            .unwrap();
+    }
+
+    fn preview_csv_import(&mut self) {
+        self.messenger
+            .gui_send
+            .send(GuiCommand::PreviewCsvImport)
+            .unwrap();
+    }
+
+    fn apply_csv_import(&mut self) {
+        let Some(changes) = self.csv_import_preview.take() else {
+            return;
+        };
+
+        self.messenger
+            .gui_send
+            .send(GuiCommand::ApplyCsvImport(changes))
+            .unwrap();
+        if let Some(group) = &self.selected_group {
+            self.messenger
+                .gui_send
+                .send(GuiCommand::GetSelectedGroupInfo(group.clone()))
+                .unwrap();
+        }
+    }
+
+    fn preview_list_import(&mut self) {
+        self.messenger
+            .gui_send
+            .send(GuiCommand::PreviewListImport)
+            .unwrap();
+    }
+
+    fn preview_mihon_import(&mut self) {
+        self.messenger
+            .gui_send
+            .send(GuiCommand::PreviewMihonImport)
+            .unwrap();
+    }
+
+    fn apply_list_import(&mut self) {
+        let Some(list_import) = self.list_import.take() else {
+            return;
+        };
+
+        let group = if self.list_import_into_new_group {
+            None
+        } else {
+            self.selected_group.clone()
+        };
+        let entries = list_import
+            .entries
+            .into_iter()
+            .filter(|x| x.selected)
+            .map(|x| MangaEntry {
+                name: x.name,
+                score: list_import.scale.map_score(x.list_score),
+                comment: x.comment,
+                manga_group: 0,
+                id: 0,
+                source_path: None,
+                missing_from_source: false,
+            })
+            .collect();
+        self.messenger
+            .gui_send
+            .send(GuiCommand::ImportEntries(group, entries))
+            .unwrap();
+    }
+
     fn add_names_from_folder(&mut self) {
         self.messenger
             .gui_send
             .send(GuiCommand::AddNamesFromFolder(
                 self.selected_group.as_ref().unwrap().clone(),
+                self.name_cleanup_rules.clone(),
+                self.remember_names_folder,
+            ))
+            .unwrap();
+    }
+
+    fn import_from_sources(&mut self) {
+        self.messenger
+            .gui_send
+            .send(GuiCommand::ImportFromSources(
+                self.selected_group.as_ref().unwrap().clone(),
+                self.source_import_options,
+            ))
+            .unwrap();
+    }
+
+    fn sync_group_folder(&mut self) {
+        self.messenger
+            .gui_send
+            .send(GuiCommand::SyncGroupFolder(
+                self.selected_group.as_ref().unwrap().clone(),
+                self.name_cleanup_rules.clone(),
             ))
             .unwrap();
     }
@@ -206,10 +686,15 @@ impl MangaUI {
             .gui_send
             .send(GuiCommand::GetUpdatedMangaGroups)
             .unwrap();
+        self.messenger
+            .gui_send
+            .send(GuiCommand::GetExportTemplates)
+            .unwrap();
     }
 
     fn select_group(&mut self, group: MangaGroup) {
         self.selected_group = Some(group);
+        self.highlighted_entry = None;
         self.messenger
             .gui_send
             .send(GuiCommand::GetSelectedGroupInfo(
@@ -337,6 +822,10 @@ impl MangaUI {
         }
 
         let entry = self.entry_to_delete.take().unwrap();
+        if self.inbox_target.as_ref().is_some_and(|x| x.id == entry.id) {
+            self.inbox_target = None;
+            self.messenger.set_inbox_target(None);
+        }
         self.messenger
             .gui_send
             .send(GuiCommand::DeleteMangaEntry(entry))
@@ -352,7 +841,24 @@ impl MangaUI {
     fn process_backend_commands(&mut self, ctx: &egui::Context) {
         while let Ok(cmd) = self.messenger.backend_recv.try_recv() {
             match cmd {
-                BackendCommand::UpdateGroups(groups) => self.manga_groups = groups,
+                BackendCommand::UpdateGroups(groups) => {
+                    // Selected group is a copy, so changes to it are picked up here
+                    if let Some(selected_group) = &mut self.selected_group
+                        && let Some(group) = groups.iter().find(|x| x.id == selected_group.id)
+                    {
+                        *selected_group = group.clone();
+                    }
+                    self.manga_groups = groups;
+                }
+                BackendCommand::ShowFolderSync(plan) => self.folder_sync = Some(plan),
+                BackendCommand::ShowFullImage(image_id, pixels) => {
+                    if let Some(viewer) = &mut self.image_viewer {
+                        viewer.set_image(ctx, image_id, pixels);
+                    }
+                }
+                BackendCommand::ShowImageEditor(image, pixels) => {
+                    self.image_editor = Some(ImageEditor::new(image, pixels));
+                }
                 BackendCommand::UpdateSelectedGroup(entries) => {
                     self.manga_entries = Some(
                         entries
@@ -393,6 +899,80 @@ impl MangaUI {
                         }
                     }
                 }
+                BackendCommand::UpdateIngestSettings(settings) => self.ingest_settings = settings,
+                BackendCommand::UpdateInboxSettings(settings) => self.inbox_settings = settings,
+                BackendCommand::ShowInboxItems(items) => {
+                    self.inbox_items.clear();
+                    self.add_inbox_items(ctx, items);
+                }
+                BackendCommand::AddInboxItems(items) => self.add_inbox_items(ctx, items),
+                BackendCommand::RemoveInboxItems(paths) => {
+                    self.inbox_items.retain(|x| !paths.contains(&x.path));
+                }
+                BackendCommand::UpdateExportTemplates(templates) => {
+                    if !templates.contains(&self.selected_export_template) {
+                        self.selected_export_template =
+                            crate::manga_group_export::BUILTIN_TEMPLATE_NAME.to_string();
+                    }
+                    self.export_templates = templates;
+                }
+                BackendCommand::ShowError(message) => self.error_message = Some(message),
+                BackendCommand::ShowListImportPreview(list_import) => {
+                    self.list_import_into_new_group = self.selected_group.is_none();
+                    self.list_import = Some(list_import);
+                }
+                BackendCommand::SelectGroup(group) => self.select_group(group),
+                BackendCommand::ShowExportPreview(preview) => {
+                    self.export_preview = Some(preview);
+                }
+                BackendCommand::UpdateExportHistory(group_id, exports) => {
+                    // Exports of several groups update the history of each
+                    if self
+                        .selected_group
+                        .as_ref()
+                        .is_some_and(|x| x.id == group_id)
+                    {
+                        self.export_history = exports;
+                    }
+                }
+                BackendCommand::ShowArchivePages(pages) => {
+                    let textures = pages
+                        .pages
+                        .iter()
+                        .enumerate()
+                        .map(|(index, page)| {
+                            ctx.load_texture(
+                                format!("archive_page_{index}"),
+                                page.thumbnail.clone(),
+                                egui::TextureOptions::default(),
+                            )
+                        })
+                        .collect();
+                    self.archive_picker = Some(ArchivePicker {
+                        selected: vec![false; pages.pages.len()],
+                        pages,
+                        textures,
+                    });
+                }
+                BackendCommand::ShowFolderNameReview(review) => {
+                    self.folder_name_review = Some(review);
+                }
+                BackendCommand::ShowLintReport(report) => {
+                    if report.problems.is_empty() {
+                        self.lint_report = None;
+                        self.run_lint_action(report.action);
+                    } else {
+                        self.lint_report = Some(report);
+                    }
+                }
+                BackendCommand::ShowCsvImportPreview(changes) => {
+                    self.csv_import_preview = Some(changes);
+                }
+                BackendCommand::ShowTextExport((format, messages)) => {
+                    self.copied_text_messages = vec![false; messages.len()];
+                    self.text_export = Some((format, messages));
+                    self.copy_text_message(0);
+                }
             }
             ctx.request_repaint();
         }
@@ -440,27 +1020,1732 @@ impl MangaUI {
         }
     }
 
-    fn draw_manga_groups_panel(&mut self, _: &egui::Context, ui: &mut egui::Ui) {
-        ui.heading(format!("Manga groups ({} total):", self.manga_groups.len()));
-        ui.separator();
+    fn draw_image_delete_confirm(&mut self, ctx: &egui::Context) {
+        let Some((image, entry)) = self.image_to_delete.clone() else {
+            return;
+        };
 
-        ui.horizontal(|ui| {
-            if ui.button("🔄 Refresh").clicked() {
-                self.refresh_manga_groups();
-            }
-            if ui.button("➕ Add new group").clicked() {
-                self.create_new_manga_group();
-            }
-            if ui.button("📥 Export").clicked() {
-                self.export_group();
-            }
-        });
-        ui.separator();
+        egui::Window::new(format!("Delete image #{} of {}", image.id, entry.name))
+            .collapsible(false)
+            .resizable(false)
+            .default_pos((0., 150.))
+            .show(ctx, |ui| {
+                ui.label("The image file is deleted as well.");
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        self.image_to_delete = None;
+                    }
 
-        // TODO: This variable should not be here, but otherwise I get errors like
-        // "cannot borrow mutably twice" or "cannot borrow immutable as mutable",
-        // because we borrow '&self' for loop, then in the closure we need to borrow
-        // '&mut self' for select_group() call.
+                    if ui.button("Yes!").clicked() {
+                        self.messenger.delete_image(&image, &entry);
+                        self.image_to_delete = None;
+                    }
+                });
+            });
+    }
+
+    fn draw_text_export(&mut self, ctx: &egui::Context) {
+        let Some((format, messages)) = &self.text_export else {
+            return;
+        };
+
+        let mut close = false;
+        let mut message_to_copy = None;
+        egui::Window::new(format!("{} export", format.name()))
+            .collapsible(false)
+            .default_size((600., 500.))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} message(s). The first one is already copied, paste them in order.",
+                    messages.len()
+                ));
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(400.)
+                    .show(ui, |ui| {
+                        for (index, message) in messages.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                let copied = if self.copied_text_messages[index] {
+                                    "✔"
+                                } else {
+                                    ""
+                                };
+                                if ui
+                                    .button(format!(
+                                        "📋 Copy part {}/{} {copied}",
+                                        index + 1,
+                                        messages.len()
+                                    ))
+                                    .clicked()
+                                {
+                                    message_to_copy = Some(index);
+                                }
+                                ui.label(format!("{} characters", message.chars().count()));
+                            });
+                            ui.add(
+                                egui::Label::new(egui::RichText::new(message).monospace())
+                                    .truncate(),
+                            );
+                            ui.separator();
+                        }
+                    });
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+
+        if let Some(index) = message_to_copy {
+            self.copy_text_message(index);
+        }
+        if close {
+            self.text_export = None;
+        }
+    }
+
+    fn draw_csv_import_preview(&mut self, ctx: &egui::Context) {
+        let Some(changes) = &self.csv_import_preview else {
+            return;
+        };
+
+        let applicable = changes
+            .iter()
+            .filter(|x| !matches!(x, CsvImportChange::Skip { .. }))
+            .count();
+        let mut cancel = false;
+        let mut apply = false;
+        egui::Window::new("CSV import preview")
+            .collapsible(false)
+            .default_size((600., 500.))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{applicable} change(s) will be applied, {} line(s) skipped.",
+                    changes.len() - applicable
+                ));
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(400.)
+                    .show(ui, |ui| {
+                        for change in changes {
+                            let text = egui::RichText::new(change.describe());
+                            let text = if matches!(change, CsvImportChange::Skip { .. }) {
+                                text.color(Color32::DARK_RED)
+                            } else {
+                                text
+                            };
+                            ui.label(text);
+                        }
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                    if ui
+                        .add_enabled(applicable > 0, egui::Button::new("Apply"))
+                        .clicked()
+                    {
+                        apply = true;
+                    }
+                });
+            });
+
+        if apply {
+            self.apply_csv_import();
+        } else if cancel {
+            self.csv_import_preview = None;
+        }
+    }
+
+    fn draw_list_import_preview(&mut self, ctx: &egui::Context) {
+        let Some(list_import) = &mut self.list_import else {
+            return;
+        };
+
+        let mut cancel = false;
+        let mut apply = false;
+        egui::Window::new(format!("Import from {}", list_import.source))
+            .collapsible(false)
+            .default_size((600., 500.))
+            .show(ctx, |ui| {
+                let has_scores = list_import.entries.iter().any(|x| x.list_score > 0.);
+                if has_scores {
+                    ui.horizontal(|ui| {
+                        ui.label("Scores in the list use:");
+                        egui::ComboBox::from_id_salt("list_import_scale")
+                            .selected_text(list_import.scale.name())
+                            .show_ui(ui, |ui| {
+                                for scale in ListScoreScale::ALL {
+                                    ui.selectable_value(
+                                        &mut list_import.scale,
+                                        scale,
+                                        scale.name(),
+                                    );
+                                }
+                            });
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Import into:");
+                    ui.radio_value(&mut self.list_import_into_new_group, true, "New group");
+                    if let Some(group) = &self.selected_group {
+                        ui.radio_value(
+                            &mut self.list_import_into_new_group,
+                            false,
+                            format!("Group #{:03}", group.id),
+                        );
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Select all").clicked() {
+                        list_import
+                            .entries
+                            .iter_mut()
+                            .for_each(|x| x.selected = true);
+                    }
+                    if ui.button("Select none").clicked() {
+                        list_import
+                            .entries
+                            .iter_mut()
+                            .for_each(|x| x.selected = false);
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(350.)
+                    .show(ui, |ui| {
+                        for entry in &mut list_import.entries {
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut entry.selected, &entry.name);
+                                if has_scores {
+                                    ui.label(format!(
+                                        "{} → {}/10",
+                                        entry.list_score,
+                                        list_import.scale.map_score(entry.list_score)
+                                    ));
+                                }
+                                if !entry.categories.is_empty() {
+                                    ui.label(format!("[{}]", entry.categories.join(", ")));
+                                }
+                                if let Some(last_read) = entry.last_read {
+                                    ui.label(format!("read {}", last_read.date()));
+                                }
+                                if !entry.comment.is_empty() {
+                                    ui.label("💬").on_hover_text(&entry.comment);
+                                }
+                            });
+                        }
+                    });
+                ui.separator();
+                let selected = list_import.entries.iter().filter(|x| x.selected).count();
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            selected > 0,
+                            egui::Button::new(format!("Import {selected} entries")),
+                        )
+                        .clicked()
+                    {
+                        apply = true;
+                    }
+                });
+            });
+
+        if apply {
+            self.apply_list_import();
+        } else if cancel {
+            self.list_import = None;
+        }
+    }
+
+    fn draw_export_preview(&mut self, ctx: &egui::Context) {
+        let Some(preview) = &self.export_preview else {
+            return;
+        };
+
+        // Preview is rendered with stored image paths, which the loaded thumbnails have too
+        let textures: std::collections::HashMap<&str, &egui::TextureHandle> = self
+            .manga_entries
+            .iter()
+            .flatten()
+            .flat_map(|entry| {
+                core::iter::zip(entry.thumbnails.iter(), entry.textures.iter())
+                    .map(|(thumbnail, texture)| (thumbnail.image.path.as_str(), texture))
+            })
+            .collect();
+
+        let mut close = false;
+        let mut export = false;
+        egui::Window::new(format!("Preview: {}", preview.template))
+            .collapsible(false)
+            .default_size((700., 600.))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} slides, steps under images show when they appear or disappear.",
+                    preview.slides.len()
+                ));
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(500.)
+                    .show(ui, |ui| {
+                        for (index, slide) in preview.slides.iter().enumerate() {
+                            ui.group(|ui| {
+                                ui.set_width(ui.available_width());
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("#{}", index + 1));
+                                    ui.heading(&slide.heading);
+                                });
+                                for paragraph in &slide.paragraphs {
+                                    ui.label(paragraph);
+                                }
+                                ui.horizontal_wrapped(|ui| {
+                                    for image in &slide.images {
+                                        ui.vertical(|ui| {
+                                            match textures.get(image.src.as_str()) {
+                                                Some(texture) => {
+                                                    ui.image(*texture);
+                                                }
+                                                None => {
+                                                    ui.label(format!("🖼 {}", image.src));
+                                                }
+                                            }
+                                            match (&image.fragment, image.step) {
+                                                (Some(fragment), Some(step)) => {
+                                                    ui.small(format!("step {step}: {fragment}"));
+                                                }
+                                                _ => {
+                                                    ui.small("always shown");
+                                                }
+                                            }
+                                        });
+                                    }
+                                });
+                            });
+                        }
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.selected_group.is_some(),
+                            egui::Button::new("💾 Export"),
+                        )
+                        .clicked()
+                    {
+                        export = true;
+                    }
+                });
+            });
+
+        if export {
+            self.export_group();
+        }
+        if close || export {
+            self.export_preview = None;
+        }
+    }
+
+    fn draw_lint_report(&mut self, ctx: &egui::Context) {
+        let Some(report) = &self.lint_report else {
+            return;
+        };
+
+        let title = match report.action {
+            LintAction::Export => "Check before export",
+            LintAction::WebShare => "Check before sharing",
+        };
+        let mut close = false;
+        let mut check_again = false;
+        let mut run_action = false;
+        let mut fix_entry = None;
+        egui::Window::new(title)
+            .collapsible(false)
+            .default_size((500., 300.))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Found {} problems in group #{}:",
+                    report.problems.len(),
+                    report.group.id
+                ));
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(300.)
+                    .show(ui, |ui| {
+                        egui::Grid::new("lint_report_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for problem in &report.problems {
+                                    let name = if problem.entry_name.is_empty() {
+                                        format!("#{:03}", problem.entry_id)
+                                    } else {
+                                        format!("#{:03} {}", problem.entry_id, problem.entry_name)
+                                    };
+                                    ui.label(name);
+                                    ui.label(problem.kind.describe());
+                                    if ui.button("➜ Fix").clicked() {
+                                        fix_entry = Some(problem.entry_id);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                    if ui.button("🔄 Check again").clicked() {
+                        check_again = true;
+                    }
+                    if ui.button(report.action.name()).clicked() {
+                        run_action = true;
+                    }
+                });
+            });
+
+        let action = report.action;
+        if let Some(entry_id) = fix_entry {
+            self.highlighted_entry = Some(entry_id);
+            self.scroll_to_highlighted_entry = true;
+        }
+        if check_again {
+            self.lint_group(action);
+        }
+        if run_action {
+            self.run_lint_action(action);
+        }
+        if close || check_again || run_action {
+            self.lint_report = None;
+        }
+    }
+
+    fn draw_archive_picker(&mut self, ctx: &egui::Context) {
+        let Some(picker) = &mut self.archive_picker else {
+            return;
+        };
+
+        let file_name = picker
+            .pages
+            .path
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let selected_count = picker.selected.iter().filter(|x| **x).count();
+        let mut close = false;
+        let mut add = false;
+        egui::Window::new(format!("Pages of {file_name}"))
+            .collapsible(false)
+            .default_size((700., 600.))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{selected_count} of {} pages selected, click a page to select it.",
+                        picker.pages.pages.len()
+                    ));
+                    if ui.button("Select all").clicked() {
+                        picker.selected.iter_mut().for_each(|x| *x = true);
+                    }
+                    if ui.button("Select none").clicked() {
+                        picker.selected.iter_mut().for_each(|x| *x = false);
+                    }
+                });
+                if !picker.pages.skipped.is_empty() {
+                    ui.colored_label(
+                        Color32::YELLOW,
+                        format!(
+                            "{} pages can't be decoded and are skipped:",
+                            picker.pages.skipped.len()
+                        ),
+                    );
+                    for (name, reason) in &picker.pages.skipped {
+                        ui.label(format!("{name}: {reason}"));
+                    }
+                }
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(500.)
+                    .show(ui, |ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            for ((page, texture), selected) in picker
+                                .pages
+                                .pages
+                                .iter()
+                                .zip(picker.textures.iter())
+                                .zip(picker.selected.iter_mut())
+                            {
+                                let button = egui::Button::image(texture).selected(*selected);
+                                if ui.add(button).on_hover_text(&page.name).clicked() {
+                                    *selected = !*selected;
+                                }
+                            }
+                        });
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                    let add_button = egui::Button::new(format!("➕ Add {selected_count} pages"));
+                    if ui.add_enabled(selected_count > 0, add_button).clicked() {
+                        add = true;
+                    }
+                });
+            });
+
+        if add {
+            let names = picker
+                .pages
+                .pages
+                .iter()
+                .zip(picker.selected.iter())
+                .filter(|(_, selected)| **selected)
+                .map(|(page, _)| page.name.clone())
+                .collect();
+            self.messenger.add_archive_pages(&picker.pages, names);
+        }
+        if close || add {
+            self.archive_picker = None;
+        }
+    }
+
+    fn draw_source_import(&mut self, ctx: &egui::Context) {
+        let mut close = false;
+        let mut import = false;
+        egui::Window::new("Import with sample pages")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Creates an entry for each subfolder or CBZ/ZIP archive of the picked folder.",
+                );
+                let options = &mut self.source_import_options;
+                egui::Grid::new("source_import_grid").show(ui, |ui| {
+                    ui.label("Pages per entry:");
+                    ui.add(egui::DragValue::new(&mut options.page_count).range(1..=20));
+                    ui.end_row();
+
+                    ui.label("Pick:");
+                    egui::ComboBox::from_id_salt("source_import_sampling")
+                        .selected_text(options.sampling.name())
+                        .show_ui(ui, |ui| {
+                            for sampling in PageSampling::ALL {
+                                ui.selectable_value(
+                                    &mut options.sampling,
+                                    sampling,
+                                    sampling.name(),
+                                );
+                            }
+                        });
+                    ui.end_row();
+                });
+                ui.weak("The same settings are used by \"Re-pick pages\" of imported entries.");
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                    if ui.button("🗀 Pick folder and import").clicked() {
+                        import = true;
+                    }
+                });
+            });
+
+        if import {
+            self.import_from_sources();
+        }
+        if close || import {
+            self.show_source_import = false;
+        }
+    }
+
+    fn draw_ingest_settings(&mut self, ctx: &egui::Context) {
+        let Some(settings) = &mut self.ingest_settings_edit else {
+            return;
+        };
+
+        let mut close = false;
+        let mut save = false;
+        egui::Window::new("Image storage settings")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.radio_value(
+                    &mut settings.keep_originals,
+                    true,
+                    "Keep original files (JPEG, PNG, GIF, WebP)",
+                );
+                ui.weak("Other formats, pasted and edited images are stored as PNG.");
+                ui.radio_value(&mut settings.keep_originals, false, "Re-encode images");
+                ui.add_enabled_ui(!settings.keep_originals, |ui| {
+                    egui::Grid::new("ingest_settings_grid").show(ui, |ui| {
+                        ui.label("Format:");
+                        egui::ComboBox::from_id_salt("ingest_codec")
+                            .selected_text(settings.codec.name())
+                            .show_ui(ui, |ui| {
+                                for codec in ImageOutputFormat::ALL {
+                                    ui.selectable_value(&mut settings.codec, codec, codec.name());
+                                }
+                            });
+                        ui.end_row();
+
+                        if settings.codec.has_quality() {
+                            ui.label("Quality:");
+                            ui.add(egui::DragValue::new(&mut settings.quality).range(1..=100));
+                            ui.end_row();
+                        }
+
+                        ui.label("Max size:");
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::DragValue::new(&mut settings.max_dimension)
+                                    .range(0..=20000)
+                                    .suffix(" px"),
+                            );
+                            ui.weak("0 keeps the size");
+                        });
+                        ui.end_row();
+                    });
+                });
+                ui.weak("Applies to images added from now on.");
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                    if ui.button("💾 Save").clicked() {
+                        save = true;
+                    }
+                });
+            });
+
+        if save {
+            self.ingest_settings = *settings;
+            self.messenger
+                .gui_send
+                .send(GuiCommand::SaveIngestSettings(*settings))
+                .unwrap();
+        }
+        if close || save {
+            self.ingest_settings_edit = None;
+        }
+    }
+
+    fn add_inbox_items(&mut self, ctx: &egui::Context, items: Vec<crate::inbox::InboxItem>) {
+        for item in items {
+            // Files changed again while waiting in the tray are only listed once
+            self.inbox_items.retain(|x| x.path != item.path);
+            self.inbox_items.push(InboxTrayItem {
+                texture: ctx.load_texture(
+                    format!("inbox_{}", item.path.display()),
+                    item.thumbnail,
+                    egui::TextureOptions::default(),
+                ),
+                path: item.path,
+                selected: false,
+            });
+        }
+    }
+
+    fn draw_inbox_settings(&mut self, ctx: &egui::Context) {
+        let Some(settings) = &mut self.inbox_settings_edit else {
+            return;
+        };
+
+        let mut close = false;
+        let mut save = false;
+        egui::Window::new("Screenshot inbox")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    "New images in the inbox folder wait in a tray at the bottom, \
+                    or go straight into the entry marked as the inbox target.",
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Folder:");
+                    match &settings.folder {
+                        Some(folder) => ui.monospace(folder.display().to_string()),
+                        None => ui.weak("none, the inbox is off"),
+                    };
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("🗀 Pick folder").clicked()
+                        && let Some(folder) = rfd::FileDialog::new()
+                            .set_title("Select inbox folder")
+                            .pick_folder()
+                    {
+                        settings.folder = Some(folder);
+                    }
+                    if ui
+                        .add_enabled(settings.folder.is_some(), egui::Button::new("Turn off"))
+                        .clicked()
+                    {
+                        settings.folder = None;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("After import:");
+                    egui::ComboBox::from_id_salt("inbox_after_import")
+                        .selected_text(settings.after_import.name())
+                        .show_ui(ui, |ui| {
+                            for action in AfterImport::ALL {
+                                ui.selectable_value(
+                                    &mut settings.after_import,
+                                    action,
+                                    action.name(),
+                                );
+                            }
+                        });
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                    if ui.button("💾 Save").clicked() {
+                        save = true;
+                    }
+                });
+            });
+
+        if save {
+            self.messenger
+                .gui_send
+                .send(GuiCommand::SaveInboxSettings(settings.clone()))
+                .unwrap();
+        }
+        if close || save {
+            self.inbox_settings_edit = None;
+        }
+    }
+
+    fn draw_inbox_tray(&mut self, ui: &mut egui::Ui) {
+        let selected = self
+            .inbox_items
+            .iter()
+            .filter(|x| x.selected)
+            .map(|x| x.path.clone())
+            .collect::<Vec<_>>();
+
+        ui.horizontal(|ui| {
+            ui.strong(format!("📥 Inbox ({} files)", self.inbox_items.len()));
+            ui.separator();
+            match &self.inbox_target {
+                Some(entry) => {
+                    ui.label(format!("New files go to '{}'", entry.name));
+                    if ui
+                        .small_button("✖")
+                        .on_hover_text("Keep them here")
+                        .clicked()
+                    {
+                        self.inbox_target = None;
+                        self.messenger.set_inbox_target(None);
+                    }
+                }
+                None => {
+                    ui.weak("New files wait here, mark an entry as the inbox target to skip this");
+                }
+            }
+            ui.separator();
+            if ui.button("Select all").clicked() {
+                self.inbox_items.iter_mut().for_each(|x| x.selected = true);
+            }
+            let add_enabled = !selected.is_empty() && self.manga_entries.is_some();
+            ui.add_enabled_ui(add_enabled, |ui| {
+                ui.menu_button(format!("➕ Add {} to", selected.len()), |ui| {
+                    egui::ScrollArea::vertical()
+                        .max_height(300.)
+                        .show(ui, |ui| {
+                            for entry in self.manga_entries.iter().flatten() {
+                                if ui.button(&entry.entry.name).clicked() {
+                                    self.messenger
+                                        .add_inbox_files(&entry.entry, selected.clone());
+                                    ui.close();
+                                }
+                            }
+                        });
+                });
+            });
+        });
+
+        egui::ScrollArea::horizontal()
+            .id_salt("inbox_scroll_area")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if self.inbox_items.is_empty() {
+                        ui.weak("No new files.");
+                    }
+                    for item in &mut self.inbox_items {
+                        let file_name = item
+                            .path
+                            .file_name()
+                            .map(|x| x.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        let button = egui::Button::image(&item.texture).selected(item.selected);
+                        if ui.add(button).on_hover_text(file_name).clicked() {
+                            item.selected = !item.selected;
+                        }
+                    }
+                });
+            });
+    }
+
+    fn draw_name_cleanup_rules(&mut self, ctx: &egui::Context) {
+        let mut close = false;
+        let mut pick_folder = false;
+        egui::Window::new("Add names from folder")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let rules = &mut self.name_cleanup_rules;
+                ui.checkbox(
+                    &mut rules.strip_brackets,
+                    "Remove [tags], (tags) and {tags}",
+                );
+                ui.checkbox(
+                    &mut rules.strip_volume_chapter,
+                    "Remove volume and chapter markers (v01, Vol. 2, ch.15)",
+                );
+                egui::Grid::new("name_cleanup_rules_grid").show(ui, |ui| {
+                    ui.label("Release groups to remove\nfrom the start, one per line:");
+                    ui.add(egui::TextEdit::multiline(&mut rules.release_groups).desired_rows(3));
+                    ui.end_row();
+
+                    ui.label("Similar names match at:");
+                    ui.add(
+                        egui::Slider::new(&mut rules.similarity_threshold, 0.5..=1.)
+                            .custom_formatter(|x, _| format!("{:.0}%", x * 100.)),
+                    );
+                    ui.end_row();
+
+                    ui.label("Folder depth:");
+                    ui.add(egui::DragValue::new(&mut rules.max_depth).range(1..=5))
+                        .on_hover_text(
+                            "Folders with subfolders are looked into until this depth is reached",
+                        );
+                    ui.end_row();
+
+                    ui.label("Folders to skip,\none glob per line:");
+                    ui.add(egui::TextEdit::multiline(&mut rules.ignore_globs).desired_rows(3));
+                    ui.end_row();
+
+                    ui.label("Try a name:");
+                    ui.text_edit_singleline(&mut self.name_cleanup_sample);
+                    ui.end_row();
+
+                    ui.label("Cleaned up:");
+                    ui.label(rules.clean_name(&self.name_cleanup_sample));
+                    ui.end_row();
+                });
+                ui.separator();
+                ui.checkbox(
+                    &mut self.remember_names_folder,
+                    "Remember the folder to sync the group with it later",
+                );
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                    if ui.button("🗀 Pick folder").clicked() {
+                        pick_folder = true;
+                    }
+                });
+            });
+
+        if pick_folder {
+            self.add_names_from_folder();
+        }
+        if close || pick_folder {
+            self.show_name_cleanup_rules = false;
+        }
+    }
+
+    fn draw_folder_name_review(&mut self, ctx: &egui::Context) {
+        let Some(review) = &mut self.folder_name_review else {
+            return;
+        };
+
+        let exact_count = review.matches.iter().filter(|x| x.is_exact()).count();
+        let create_count = review.matches.iter().filter(|x| x.create).count();
+        let link_count = review
+            .matches
+            .iter()
+            .filter(|x| !x.create && x.existing.is_some())
+            .count();
+        let mut close = false;
+        let mut create = false;
+        egui::Window::new(format!("Names from {}", review.folder.display()))
+            .collapsible(false)
+            .default_size((700., 500.))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} title folders found, {exact_count} of them already have entries.",
+                    review.matches.len()
+                ));
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(400.)
+                    .show(ui, |ui| {
+                        egui::Grid::new("folder_name_review_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Create");
+                                ui.strong("Folder");
+                                ui.strong("Entry name");
+                                ui.strong("Similar existing entry");
+                                ui.end_row();
+
+                                for name_match in &mut review.matches {
+                                    if name_match.is_exact() {
+                                        continue;
+                                    }
+                                    ui.checkbox(&mut name_match.create, "");
+                                    ui.label(name_match.folder.display().to_string());
+                                    ui.add_enabled(
+                                        name_match.create,
+                                        egui::TextEdit::singleline(&mut name_match.name),
+                                    );
+                                    match &name_match.existing {
+                                        Some(existing) => ui.label(format!(
+                                            "#{:03} {} ({:.0}%)",
+                                            existing.entry_id,
+                                            existing.entry_name,
+                                            existing.similarity * 100.
+                                        )),
+                                        None => ui.weak("none"),
+                                    };
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                    let create_button = egui::Button::new(format!(
+                        "➕ Create {create_count} entries, link {link_count}"
+                    ));
+                    if ui
+                        .add_enabled(create_count + link_count > 0, create_button)
+                        .on_hover_text("Matched entries without a folder are linked to it")
+                        .clicked()
+                    {
+                        create = true;
+                    }
+                });
+            });
+
+        if create {
+            self.messenger
+                .gui_send
+                .send(GuiCommand::ApplyFolderNameReview(
+                    self.folder_name_review.take().unwrap(),
+                ))
+                .unwrap();
+        }
+        if close || create {
+            self.folder_name_review = None;
+        }
+    }
+
+    fn draw_folder_sync(&mut self, ctx: &egui::Context) {
+        let Some(plan) = &mut self.folder_sync else {
+            return;
+        };
+
+        let apply_count = plan.items.iter().filter(|x| x.apply).count();
+        let mut close = false;
+        let mut apply = false;
+        egui::Window::new(format!("Sync with {}", plan.folder.display()))
+            .collapsible(false)
+            .default_size((700., 500.))
+            .show(ctx, |ui| {
+                if plan.items.is_empty() {
+                    ui.label("Group is up to date with the folder.");
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(400.)
+                    .show(ui, |ui| {
+                        egui::Grid::new("folder_sync_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (index, item) in plan.items.iter_mut().enumerate() {
+                                    ui.checkbox(&mut item.apply, "");
+                                    match &mut item.change {
+                                        SyncChange::Add { folder, name } => {
+                                            ui.label("➕ New");
+                                            ui.label(folder.display().to_string());
+                                            ui.text_edit_singleline(name);
+                                        }
+                                        SyncChange::Link {
+                                            entry,
+                                            folder,
+                                            similarity,
+                                        } => {
+                                            ui.label(format!(
+                                                "🔗 Link ({:.0}%)",
+                                                *similarity * 100.
+                                            ));
+                                            ui.label(folder.display().to_string());
+                                            ui.label(format!("#{:03} {}", entry.id, entry.name));
+                                        }
+                                        SyncChange::Rename {
+                                            entry,
+                                            folder,
+                                            name,
+                                            similarity,
+                                        } => {
+                                            ui.label(format!(
+                                                "✏ Renamed ({:.0}%)",
+                                                *similarity * 100.
+                                            ));
+                                            ui.label(folder.display().to_string()).on_hover_text(
+                                                format!(
+                                                    "Was '{}'",
+                                                    entry
+                                                        .source_path
+                                                        .as_deref()
+                                                        .unwrap_or_default()
+                                                ),
+                                            );
+                                            ui.horizontal(|ui| {
+                                                ui.label(format!("#{:03}", entry.id));
+                                                ui.text_edit_singleline(name);
+                                            });
+                                        }
+                                        SyncChange::Vanished { entry, action } => {
+                                            ui.label("❌ Gone");
+                                            ui.label(
+                                                entry.source_path.as_deref().unwrap_or_default(),
+                                            );
+                                            ui.horizontal(|ui| {
+                                                ui.label(format!(
+                                                    "#{:03} {}",
+                                                    entry.id, entry.name
+                                                ));
+                                                egui::ComboBox::from_id_salt(format!(
+                                                    "folder_sync_action_{index}"
+                                                ))
+                                                .selected_text(action.name())
+                                                .show_ui(ui, |ui| {
+                                                    for value in VanishedAction::ALL {
+                                                        ui.selectable_value(
+                                                            action,
+                                                            value,
+                                                            value.name(),
+                                                        );
+                                                    }
+                                                });
+                                            });
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                if !plan.restored.is_empty() {
+                    ui.label(format!(
+                        "{} flagged entries have their folders back.",
+                        plan.restored.len()
+                    ));
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                    let apply_button = egui::Button::new(format!("✔ Apply {apply_count} changes"));
+                    let has_changes = apply_count > 0 || !plan.restored.is_empty();
+                    if ui.add_enabled(has_changes, apply_button).clicked() {
+                        apply = true;
+                    }
+                });
+            });
+
+        if apply {
+            self.messenger
+                .gui_send
+                .send(GuiCommand::ApplyFolderSync(
+                    self.folder_sync.take().unwrap(),
+                ))
+                .unwrap();
+        }
+        if close {
+            self.folder_sync = None;
+        }
+    }
+
+    fn draw_image_viewer(&mut self, ctx: &egui::Context) {
+        let Some(viewer) = &mut self.image_viewer else {
+            return;
+        };
+
+        let mut close = false;
+        let mut load = false;
+        let mut delete = false;
+        let mut edit = false;
+        egui::Window::new(format!(
+            "{}, image {} of {}",
+            viewer.entry.name,
+            viewer.index + 1,
+            viewer.images.len()
+        ))
+        .id(egui::Id::new("image_viewer"))
+        .collapsible(false)
+        .default_size((1000., 720.))
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(viewer.has_previous(), egui::Button::new("⏴ Previous"))
+                    .clicked()
+                {
+                    viewer.show_previous();
+                    load = true;
+                }
+                if ui
+                    .add_enabled(viewer.has_next(), egui::Button::new("Next ⏵"))
+                    .clicked()
+                {
+                    viewer.show_next();
+                    load = true;
+                }
+                ui.separator();
+                if ui.button("➖").on_hover_text("Zoom out").clicked() {
+                    viewer.zoom_by(1. / 1.25, EguiVec2::ZERO);
+                }
+                ui.label(format!("{:.0}%", viewer.zoom * 100.));
+                if ui.button("➕").on_hover_text("Zoom in").clicked() {
+                    viewer.zoom_by(1.25, EguiVec2::ZERO);
+                }
+                if ui.button("Fit").clicked() {
+                    viewer.reset_zoom();
+                }
+                ui.separator();
+                if ui.button("✏ Edit image").clicked() {
+                    edit = true;
+                }
+                if viewer.confirm_delete {
+                    ui.label("Delete this image?");
+                    if ui.button("Cancel").clicked() {
+                        viewer.confirm_delete = false;
+                    }
+                    if ui.button("Yes!").clicked() {
+                        delete = true;
+                    }
+                } else if ui.button("🗑 Delete").clicked() {
+                    viewer.confirm_delete = true;
+                }
+            });
+            ui.weak(
+                "Scroll to zoom, drag to move, double click to fit, arrow keys to switch images.",
+            );
+
+            let (response, painter) = ui.allocate_painter(
+                EguiVec2::new(ui.available_width(), 620.),
+                egui::Sense::click_and_drag(),
+            );
+            let rect = response.rect;
+            painter.rect_filled(rect, 0., Color32::from_gray(0x20));
+            match &viewer.texture {
+                Some(texture) => {
+                    // Small images are shown at their size, large ones fit into the view
+                    let size = texture.size_vec2();
+                    let fit = (rect.width() / size.x).min(rect.height() / size.y).min(1.);
+                    painter.image(
+                        texture.id(),
+                        egui::Rect::from_center_size(
+                            rect.center() + viewer.pan,
+                            size * fit * viewer.zoom,
+                        ),
+                        egui::Rect::from_min_max(egui::pos2(0., 0.), egui::pos2(1., 1.)),
+                        Color32::WHITE,
+                    );
+                }
+                None => {
+                    painter.text(
+                        rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        "Loading…",
+                        egui::FontId::proportional(16.),
+                        Color32::LIGHT_GRAY,
+                    );
+                }
+            }
+
+            if response.dragged() {
+                viewer.pan += response.drag_delta();
+            }
+            if response.double_clicked() {
+                viewer.reset_zoom();
+            }
+            if let Some(pointer) = response.hover_pos() {
+                let scroll = ui.input(|i| i.smooth_scroll_delta.y);
+                if scroll != 0. {
+                    viewer.zoom_by((scroll / 200.).exp(), pointer - rect.center());
+                }
+            }
+
+            ui.separator();
+            if ui.button("Close").clicked() {
+                close = true;
+            }
+        });
+
+        if !ctx.wants_keyboard_input() {
+            let (previous, next, escape) = ctx.input(|i| {
+                (
+                    i.key_pressed(egui::Key::ArrowLeft),
+                    i.key_pressed(egui::Key::ArrowRight),
+                    i.key_pressed(egui::Key::Escape),
+                )
+            });
+            if previous && viewer.has_previous() {
+                viewer.show_previous();
+                load = true;
+            }
+            if next && viewer.has_next() {
+                viewer.show_next();
+                load = true;
+            }
+            close |= escape;
+        }
+
+        if delete {
+            self.messenger.delete_image(viewer.current(), &viewer.entry);
+            if viewer.remove_current() {
+                load = true;
+            } else {
+                close = true;
+            }
+        }
+        if edit {
+            self.messenger.open_image_editor(viewer.current());
+            close = true;
+        }
+        if load && !close {
+            self.messenger.load_full_image(viewer.current());
+        }
+        if close {
+            self.image_viewer = None;
+        }
+    }
+
+    fn draw_image_editor(&mut self, ctx: &egui::Context) {
+        let Some(editor) = &mut self.image_editor else {
+            return;
+        };
+
+        let texture = editor.texture(ctx);
+        let (width, height) = editor.current().dimensions();
+        let mut close = false;
+        let mut save = None;
+        egui::Window::new("Edit image")
+            .collapsible(false)
+            .default_size((850., 750.))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("⟲").on_hover_text("Rotate left").clicked() {
+                        editor.rotate_left();
+                    }
+                    if ui.button("⟳").on_hover_text("Rotate right").clicked() {
+                        editor.rotate_right();
+                    }
+                    if ui.button("⬌").on_hover_text("Flip horizontally").clicked() {
+                        editor.flip_horizontally();
+                    }
+                    if ui.button("⬍").on_hover_text("Flip vertically").clicked() {
+                        editor.flip_vertically();
+                    }
+                    ui.separator();
+                    for tool in EditorTool::ALL {
+                        ui.selectable_value(&mut editor.tool, tool, tool.name());
+                    }
+                    let apply_button = egui::Button::new("✔ Apply to selection");
+                    if ui
+                        .add_enabled(editor.selection.is_some(), apply_button)
+                        .clicked()
+                    {
+                        editor.apply_tool();
+                    }
+                    ui.separator();
+                    if ui
+                        .add_enabled(editor.can_undo(), egui::Button::new("↺ Undo"))
+                        .clicked()
+                    {
+                        editor.undo();
+                    }
+                });
+                ui.label(format!(
+                    "{width}×{height}, drag over the image to select an area."
+                ));
+
+                #[allow(clippy::cast_precision_loss)]
+                let (image_width, image_height) = (width as f32, height as f32);
+                let scale = (800. / image_width).min(600. / image_height).min(1.);
+                egui::ScrollArea::both().max_height(620.).show(ui, |ui| {
+                    let (response, painter) = ui.allocate_painter(
+                        EguiVec2::new(image_width * scale, image_height * scale),
+                        egui::Sense::drag(),
+                    );
+                    let rect = response.rect;
+                    painter.image(
+                        texture.id(),
+                        rect,
+                        egui::Rect::from_min_max(egui::pos2(0., 0.), egui::pos2(1., 1.)),
+                        Color32::WHITE,
+                    );
+
+                    let to_image = |pos: egui::Pos2| {
+                        ((pos.x - rect.min.x) / scale, (pos.y - rect.min.y) / scale)
+                    };
+                    if response.drag_started() {
+                        editor.drag_start = response.interact_pointer_pos().map(to_image);
+                    }
+                    if response.dragged()
+                        && let Some(start) = editor.drag_start
+                        && let Some(pos) = response.interact_pointer_pos()
+                    {
+                        editor.selection =
+                            Selection::from_corners(start, to_image(pos), width, height);
+                    }
+                    if response.drag_stopped() {
+                        editor.drag_start = None;
+                    }
+
+                    if let Some(selection) = editor.selection {
+                        #[allow(clippy::cast_precision_loss)]
+                        let selection_rect = egui::Rect::from_min_size(
+                            rect.min
+                                + EguiVec2::new(selection.x as f32, selection.y as f32) * scale,
+                            EguiVec2::new(selection.width as f32, selection.height as f32) * scale,
+                        );
+                        painter.rect_stroke(
+                            selection_rect,
+                            0.,
+                            Stroke::new(2., Color32::YELLOW),
+                            egui::StrokeKind::Middle,
+                        );
+                    }
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                    if ui
+                        .add_enabled(editor.can_undo(), egui::Button::new("💾 Replace image"))
+                        .clicked()
+                    {
+                        save = Some(true);
+                    }
+                    if ui
+                        .add_enabled(editor.can_undo(), egui::Button::new("➕ Save as new image"))
+                        .clicked()
+                    {
+                        save = Some(false);
+                    }
+                });
+            });
+
+        if let Some(replace) = save {
+            let mut editor = self.image_editor.take().unwrap();
+            self.messenger
+                .gui_send
+                .send(GuiCommand::SaveEditedImage(
+                    editor.image,
+                    editor.history.pop().unwrap(),
+                    replace,
+                ))
+                .unwrap();
+        }
+        if close {
+            self.image_editor = None;
+        }
+    }
+
+    fn draw_export_history(&mut self, ctx: &egui::Context) {
+        let mut close = false;
+        let mut repeat = None;
+        egui::Window::new("Export history")
+            .collapsible(false)
+            .default_size((600., 300.))
+            .show(ctx, |ui| {
+                if self.export_history.is_empty() {
+                    ui.label("This group wasn't exported yet.");
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(300.)
+                    .show(ui, |ui| {
+                        egui::Grid::new("export_history_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for export in &self.export_history {
+                                    ui.label(export.exported_on.to_string());
+                                    ui.label(&export.template);
+                                    ui.add(egui::Label::new(&export.path).truncate())
+                                        .on_hover_text(&export.options);
+                                    if ui.button("🔁 Re-export").clicked() {
+                                        repeat = Some(export.clone());
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+
+        if let Some(export) = repeat {
+            self.messenger
+                .gui_send
+                .send(GuiCommand::RepeatExport(export))
+                .unwrap();
+        }
+        if close {
+            self.show_export_history = false;
+        }
+    }
+
+    fn draw_epub_group_selection(&mut self, ctx: &egui::Context) {
+        let Some(selection) = &mut self.epub_group_selection else {
+            return;
+        };
+
+        let mut cancel = false;
+        let mut export = false;
+        egui::Window::new("EPUB export")
+            .collapsible(false)
+            .default_size((400., 400.))
+            .show(ctx, |ui| {
+                ui.label("Groups to put into the book, oldest first:");
+                ui.horizontal(|ui| {
+                    if ui.button("Select all").clicked() {
+                        selection.iter_mut().for_each(|x| x.1 = true);
+                    }
+                    if ui.button("Select none").clicked() {
+                        selection.iter_mut().for_each(|x| x.1 = false);
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(300.)
+                    .show(ui, |ui| {
+                        for (group, selected) in selection.iter_mut() {
+                            ui.checkbox(
+                                selected,
+                                format!("Group #{:03} ({})", group.id, group.added_on),
+                            );
+                        }
+                    });
+                ui.separator();
+                let selected = selection.iter().filter(|x| x.1).count();
+                ui.horizontal(|ui| {
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            selected > 0,
+                            egui::Button::new(format!("Export {selected} groups")),
+                        )
+                        .clicked()
+                    {
+                        export = true;
+                    }
+                });
+            });
+
+        if export {
+            let mut groups: Vec<MangaGroup> = self
+                .epub_group_selection
+                .take()
+                .unwrap()
+                .into_iter()
+                .filter(|x| x.1)
+                .map(|x| x.0)
+                .collect();
+            groups.sort_by_key(|x| (x.added_on, x.id));
+            self.export_epub(groups);
+        } else if cancel {
+            self.epub_group_selection = None;
+        }
+    }
+
+    fn draw_image_export(&mut self, ctx: &egui::Context) {
+        let mut close = false;
+        let mut export = false;
+        let options = &mut self.image_export_options;
+        egui::Window::new("Image export")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for layout in [ImageLayout::TierList, ImageLayout::Collage] {
+                        ui.radio_value(&mut options.layout, layout, layout.name());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Width:");
+                    ui.add(
+                        egui::DragValue::new(&mut options.width)
+                            .range(400..=8000)
+                            .suffix(" px"),
+                    );
+                    ui.label("Covers per row:");
+                    ui.add(egui::DragValue::new(&mut options.columns).range(1..=30));
+                });
+                if options.layout == ImageLayout::TierList {
+                    ui.label("Minimal score for each tier:");
+                    ui.horizontal(|ui| {
+                        for (index, threshold) in options.tier_thresholds.iter_mut().enumerate() {
+                            ui.label(TIER_NAMES[index]);
+                            ui.add(egui::DragValue::new(threshold).range(1..=10));
+                        }
+                        ui.label(format!("{} - the rest", TIER_NAMES[4]));
+                    });
+                    if !options.tier_thresholds.is_sorted_by(|a, b| a > b) {
+                        ui.colored_label(
+                            Color32::YELLOW,
+                            "Tiers should go from high to low scores.",
+                        );
+                    }
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.selected_group.is_some(),
+                            egui::Button::new("💾 Save PNG"),
+                        )
+                        .clicked()
+                    {
+                        export = true;
+                    }
+                });
+            });
+
+        if export {
+            self.export_group_image();
+        }
+        if close {
+            self.show_image_export = false;
+        }
+    }
+
+    fn draw_error_message(&mut self, ctx: &egui::Context) {
+        if let Some(message) = &self.error_message {
+            let mut close = false;
+            egui::Window::new("Error")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, EguiVec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label(message);
+                    if ui.button("OK").clicked() {
+                        close = true;
+                    }
+                });
+            if close {
+                self.error_message = None;
+            }
+        }
+    }
+
+    fn draw_manga_groups_panel(&mut self, _: &egui::Context, ui: &mut egui::Ui) {
+        ui.heading(format!("Manga groups ({} total):", self.manga_groups.len()));
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            if ui.button("🔄 Refresh").clicked() {
+                self.refresh_manga_groups();
+            }
+            if ui.button("➕ Add new group").clicked() {
+                self.create_new_manga_group();
+            }
+            ui.menu_button("📥 Export", |ui| {
+                let has_group = self.selected_group.is_some();
+                if ui
+                    .add_enabled(has_group, egui::Button::new("🎞 Slideshow (template)"))
+                    .clicked()
+                {
+                    self.export_group();
+                }
+                if ui
+                    .add_enabled(has_group, egui::Button::new("👁 Preview slideshow"))
+                    .clicked()
+                {
+                    self.preview_export();
+                }
+                if ui
+                    .add_enabled(has_group, egui::Button::new("🕘 Export history"))
+                    .clicked()
+                {
+                    self.show_export_history = true;
+                }
+                ui.separator();
+                if ui
+                    .add_enabled(has_group, egui::Button::new("📝 Markdown"))
+                    .clicked()
+                {
+                    self.export_group_markdown();
+                }
+                ui.checkbox(&mut self.markdown_front_matter, "YAML front matter");
+                if ui
+                    .add_enabled(has_group, egui::Button::new("📄 PDF booklet"))
+                    .clicked()
+                {
+                    self.export_group_pdf();
+                }
+                if ui
+                    .add_enabled(has_group, egui::Button::new("📖 EPUB (this group)"))
+                    .clicked()
+                {
+                    self.export_epub(vec![self.selected_group.as_ref().unwrap().clone()]);
+                }
+                if ui.button("📖 EPUB (several groups)").clicked() {
+                    self.epub_group_selection = Some(
+                        self.manga_groups
+                            .iter()
+                            .map(|group| {
+                                let selected = self
+                                    .selected_group
+                                    .as_ref()
+                                    .is_some_and(|x| x.id == group.id);
+                                (group.clone(), selected)
+                            })
+                            .collect(),
+                    );
+                }
+                if ui
+                    .add_enabled(has_group, egui::Button::new("🖼 Tier list / collage PNG"))
+                    .clicked()
+                {
+                    self.show_image_export = true;
+                }
+                ui.separator();
+                if ui
+                    .add_enabled(has_group, egui::Button::new("💬 BBCode (forums)"))
+                    .clicked()
+                {
+                    self.export_group_text(TextExportFormat::BBCode);
+                }
+                if ui
+                    .add_enabled(has_group, egui::Button::new("💬 Discord messages"))
+                    .clicked()
+                {
+                    self.export_group_text(TextExportFormat::Discord);
+                }
+                ui.separator();
+                if ui
+                    .add_enabled(has_group, egui::Button::new("📊 CSV (this group)"))
+                    .clicked()
+                {
+                    self.export_entries_csv(false);
+                }
+                if ui.button("📊 CSV (whole library)").clicked() {
+                    self.export_entries_csv(true);
+                }
+                ui.separator();
+                if ui.button("🌐 Website (article pages)").clicked() {
+                    self.export_site(SiteLayout::Article);
+                }
+                if ui
+                    .button("🌐 Website (slideshow pages)")
+                    .on_hover_text("Uses the selected template, copy reveal.js next to index.html")
+                    .clicked()
+                {
+                    self.export_site(SiteLayout::Slideshow);
+                }
+            });
+            ui.menu_button("📤 Import", |ui| {
+                if ui.button("📊 CSV").clicked() {
+                    self.preview_csv_import();
+                }
+                if ui.button("📚 MyAnimeList / AniList export").clicked() {
+                    self.preview_list_import();
+                }
+                if ui.button("📱 Mihon / Tachiyomi backup").clicked() {
+                    self.preview_mihon_import();
+                }
+                if ui.button("🎞 Exported slideshows (HTML)").clicked() {
+                    self.messenger
+                        .gui_send
+                        .send(GuiCommand::ImportHtmlExports)
+                        .unwrap();
+                }
+                ui.separator();
+                if ui.button("📥 Screenshot inbox").clicked() {
+                    self.inbox_settings_edit = Some(self.inbox_settings.clone());
+                }
+                if ui.button("⚙ Image storage settings").clicked() {
+                    self.ingest_settings_edit = Some(self.ingest_settings);
+                }
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Template:");
+            egui::ComboBox::from_id_salt("export_template")
+                .selected_text(&self.selected_export_template)
+                .show_ui(ui, |ui| {
+                    for template in &self.export_templates {
+                        ui.selectable_value(
+                            &mut self.selected_export_template,
+                            template.clone(),
+                            template,
+                        );
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            let options = &mut self.export_image_options;
+            ui.label("Images:");
+            egui::ComboBox::from_id_salt("export_image_preset")
+                .selected_text(options.preset.name())
+                .show_ui(ui, |ui| {
+                    for preset in ImagePreset::ALL {
+                        if ui
+                            .selectable_value(&mut options.preset, preset, preset.name())
+                            .changed()
+                        {
+                            options.quality = preset.default_quality();
+                        }
+                    }
+                });
+        });
+        if self.export_image_options.preset != ImagePreset::Original {
+            ui.horizontal(|ui| {
+                let options = &mut self.export_image_options;
+                egui::ComboBox::from_id_salt("export_image_format")
+                    .selected_text(options.format.name())
+                    .show_ui(ui, |ui| {
+                        for format in ImageOutputFormat::ALL {
+                            ui.selectable_value(&mut options.format, format, format.name());
+                        }
+                    });
+                if options.format.has_quality() {
+                    ui.label("Quality:");
+                    ui.add(egui::DragValue::new(&mut options.quality).range(1..=100));
+                }
+            });
+        }
+        ui.separator();
+
+        // TODO: This variable should not be here, but otherwise I get errors like
+        // "cannot borrow mutably twice" or "cannot borrow immutable as mutable",
+        // because we borrow '&self' for loop, then in the closure we need to borrow
+        // '&mut self' for select_group() call.
         let mut new_selected_group: Option<MangaGroup> = None;
         egui::ScrollArea::vertical().show(ui, |ui| {
             for group in &self.manga_groups {
@@ -541,11 +2826,22 @@ impl MangaUI {
                 );
             }
             if ui.button("🗄 Add names from folder").clicked() && self.manga_entries.is_some() {
-                self.add_names_from_folder();
+                self.show_name_cleanup_rules = true;
+            }
+            if let Some(folder) = &self.selected_group.as_ref().unwrap().source_folder
+                && ui
+                    .button("🔁 Sync with folder")
+                    .on_hover_text(format!("Compare entries with '{folder}'"))
+                    .clicked()
+            {
+                self.sync_group_folder();
+            }
+            if ui.button("📚 Import with sample pages").clicked() {
+                self.show_source_import = true;
             }
             if self.web_server.handle.is_none() {
                 if ui.button("☁ Share online").clicked() && self.manga_entries.is_some() {
-                    self.start_web_server();
+                    self.lint_group(LintAction::WebShare);
                 }
             } else {
                 let is_stopping = self
@@ -588,12 +2884,31 @@ impl MangaUI {
             self.draw_entry_delete_confirm(ctx);
         }
 
+        // Files dragged from a file manager go to the entry under the pointer
+        let (dropped_paths, files_hovered, pointer_pos) = ctx.input(|i| {
+            (
+                i.raw
+                    .dropped_files
+                    .iter()
+                    .filter_map(|x| x.path.clone())
+                    .collect::<Vec<_>>(),
+                !i.raw.hovered_files.is_empty(),
+                i.pointer.latest_pos(),
+            )
+        });
+        let mut drop_target = None;
+
         egui::ScrollArea::vertical().show(ui, |ui| {
             for entry in self.manga_entries.as_mut().unwrap().iter_mut() {
-                let stroke = (2.0f32, Color32::from_rgb(0x10, 0x10, 0x10));
+                let is_highlighted = self.highlighted_entry == Some(entry.entry.id);
+                let stroke = if is_highlighted {
+                    (3.0f32, Color32::from_rgb(0xD0, 0x20, 0x20))
+                } else {
+                    (2.0f32, Color32::from_rgb(0x10, 0x10, 0x10))
+                };
                 let fill = Color32::LIGHT_GRAY;
 
-                egui::Frame::new()
+                let frame = egui::Frame::new()
                     .inner_margin(5.)
                     .outer_margin(EguiVec2::new(0., 2.))
                     .stroke(Stroke::from(stroke))
@@ -605,13 +2920,17 @@ impl MangaUI {
                             ui.vertical_centered_justified(|ui| {
                                 ui.horizontal(|ui| {
                                     ui.label(format!("#{:03}", entry.entry.id));
+                                    if entry.entry.missing_from_source {
+                                        ui.colored_label(Color32::DARK_RED, "⚠")
+                                            .on_hover_text("Folder of this entry is gone");
+                                    }
                                     ui.label("Name: ");
                                     ui.add(egui::TextEdit::singleline(&mut entry.entry.name));
                                 });
                                 ui.horizontal(|ui| {
                                     ui.label("Score: ");
                                     ui.spacing_mut().slider_width = 280.;
-                                    ui.add(egui::Slider::new(&mut entry.entry.score, 1..=10));
+                                    ui.add(egui::Slider::new(&mut entry.entry.score, SCORE_RANGE));
                                 });
                             });
 
@@ -644,6 +2963,36 @@ impl MangaUI {
                             if ui.add(paste_image_button).clicked() {
                                 self.messenger.add_image_from_clipboard(&entry.entry);
                             }
+                            let archive_button = egui::Button::new("🗜 Add from archive");
+                            if ui.add(archive_button).clicked() {
+                                self.messenger.open_archive(&entry.entry);
+                            }
+                            if let Some(source_path) = &entry.entry.source_path {
+                                let repick_button = egui::Button::new("🎲 Re-pick pages");
+                                let repick_response = ui.add(repick_button).on_hover_text(format!(
+                                    "Replace pages taken from '{source_path}', images added by hand are kept"
+                                ));
+                                if repick_response.clicked() {
+                                    self.messenger.repick_source_pages(
+                                        &entry.entry,
+                                        self.source_import_options,
+                                    );
+                                }
+                            }
+                            ui.weak("or drop files and folders here");
+                            if self.inbox_settings.folder.is_some() {
+                                let is_target = self
+                                    .inbox_target
+                                    .as_ref()
+                                    .is_some_and(|x| x.id == entry.entry.id);
+                                let target_response = ui
+                                    .selectable_label(is_target, "📥 Inbox target")
+                                    .on_hover_text("New inbox files are added to this entry");
+                                if target_response.clicked() {
+                                    self.inbox_target = (!is_target).then(|| entry.entry.clone());
+                                    self.messenger.set_inbox_target(self.inbox_target.as_ref());
+                                }
+                            }
                         });
                         egui::ScrollArea::horizontal()
                             .id_salt(format!("images_scroll_area_{}", entry.entry.id))
@@ -651,24 +3000,77 @@ impl MangaUI {
                                 egui::Grid::new(format!("grid_{}", entry.entry.id)).show(
                                     ui,
                                     |ui| {
-                                        for (texture, image_data) in core::iter::zip(
+                                        for (index, (texture, image_data)) in core::iter::zip(
                                             entry.textures.iter(),
                                             entry.thumbnails.iter(),
-                                        ) {
+                                        )
+                                        .enumerate()
+                                        {
                                             let image = egui::Button::image(texture);
                                             let added_image = ui.add(image).on_hover_ui(|ui| {
-                                                ui.label("Click to delete");
+                                                ui.label("Click to view, right click for more");
                                             });
                                             if added_image.clicked() {
-                                                self.messenger
-                                                    .delete_image(&image_data.image, &entry.entry);
+                                                let images = entry
+                                                    .thumbnails
+                                                    .iter()
+                                                    .map(|x| x.image.clone())
+                                                    .collect();
+                                                self.image_viewer = Some(ImageViewer::new(
+                                                    entry.entry.clone(),
+                                                    images,
+                                                    index,
+                                                ));
+                                                self.messenger.load_full_image(&image_data.image);
                                             }
+                                            added_image.context_menu(|ui| {
+                                                if ui.button("✏ Edit image").clicked() {
+                                                    self.messenger
+                                                        .open_image_editor(&image_data.image);
+                                                    ui.close();
+                                                }
+                                                if ui.button("🗑 Delete image").clicked() {
+                                                    self.image_to_delete = Some((
+                                                        image_data.image.clone(),
+                                                        entry.entry.clone(),
+                                                    ));
+                                                    ui.close();
+                                                }
+                                            });
                                         }
                                     },
                                 );
                             });
                     });
+
+                if is_highlighted && self.scroll_to_highlighted_entry {
+                    frame.response.scroll_to_me(Some(egui::Align::TOP));
+                    self.scroll_to_highlighted_entry = false;
+                }
+
+                let under_pointer = pointer_pos.is_some_and(|x| frame.response.rect.contains(x));
+                if files_hovered && under_pointer {
+                    ui.painter().rect_stroke(
+                        frame.response.rect,
+                        5.,
+                        Stroke::new(3., Color32::from_rgb(0x20, 0x60, 0xD0)),
+                        egui::StrokeKind::Outside,
+                    );
+                }
+                if !dropped_paths.is_empty() && under_pointer {
+                    drop_target = Some(entry.entry.clone());
+                }
             }
         });
+
+        if !dropped_paths.is_empty() {
+            match drop_target {
+                Some(entry) => self.messenger.add_dropped_images(&entry, dropped_paths),
+                None => {
+                    self.error_message =
+                        Some("Drop images onto the entry they should be added to.".to_string());
+                }
+            }
+        }
     }
 }
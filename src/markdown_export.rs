@@ -0,0 +1,136 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+
+use std::fmt::Write;
+
+use crate::export_common::ExportImageOptions;
+use shared::types::{MangaEntry, MangaGroup, MangaImage};
+
+pub struct MarkdownExporter {
+    group: MangaGroup,
+    entries: Vec<(MangaEntry, Vec<MangaImage>)>,
+    front_matter: bool,
+    image_options: ExportImageOptions,
+    cwd: std::path::PathBuf,
+}
+
+impl MarkdownExporter {
+    pub fn new(
+        group: MangaGroup,
+        mut entries: Vec<(MangaEntry, Vec<MangaImage>)>,
+        front_matter: bool,
+        image_options: ExportImageOptions,
+    ) -> AnyResult<Self> {
+        crate::export_common::sort_entries(&mut entries);
+
+        Ok(Self {
+            group,
+            entries,
+            front_matter,
+            image_options,
+            cwd: std::env::current_dir().context("Unable to get CWD.")?,
+        })
+    }
+
+    fn title(&self) -> String {
+        format!("Manga review #{} ({})", self.group.id, self.group.added_on)
+    }
+
+    /// YAML front matter, string values are written as JSON strings,
+    /// which are valid double-quoted YAML scalars.
+    fn create_front_matter(&self) -> AnyResult<String> {
+        let scores: Vec<i64> = self.entries.iter().map(|(entry, _)| entry.score).collect();
+        #[allow(clippy::cast_precision_loss)]
+        let average_score = if scores.is_empty() {
+            0.
+        } else {
+            scores.iter().sum::<i64>() as f64 / scores.len() as f64
+        };
+        let titles: Vec<String> = self
+            .entries
+            .iter()
+            .map(|(entry, _)| serde_json::to_string(&entry.name))
+            .collect::<Result<_, _>>()?;
+
+        let mut result = String::from("---\n");
+        writeln!(result, "title: {}", serde_json::to_string(&self.title())?)?;
+        writeln!(
+            result,
+            "date: {}",
+            self.group.added_on.format("%Y-%m-%dT%H:%M:%S")
+        )?;
+        writeln!(result, "group_id: {}", self.group.id)?;
+        writeln!(result, "entry_count: {}", self.entries.len())?;
+        writeln!(result, "average_score: {average_score:.2}")?;
+        writeln!(result, "titles: [{}]", titles.join(", "))?;
+        result.push_str("---\n\n");
+        Ok(result)
+    }
+
+    fn create_entry_element(
+        manga: &MangaEntry,
+        images: &[MangaImage],
+        image_paths: &std::collections::HashMap<i64, String>,
+    ) -> AnyResult<String> {
+        let mut result = String::with_capacity(manga.comment.len() + 200);
+        writeln!(result, "## {}\n", manga.name.trim())?;
+        writeln!(result, "**Score:** {}/10\n", manga.score)?;
+        if !manga.comment.trim().is_empty() {
+            writeln!(result, "{}\n", manga.comment.trim())?;
+        }
+        let alt_text = manga.name.replace(['[', ']'], "");
+        for (index, image) in images.iter().enumerate() {
+            writeln!(
+                result,
+                "![{} {}]({})\n",
+                alt_text,
+                index + 1,
+                image_paths[&image.id].replace(' ', "%20")
+            )?;
+        }
+        Ok(result)
+    }
+
+    pub fn export_group(&self) -> AnyResult<Option<std::path::PathBuf>> {
+        let export_folder = rfd::FileDialog::new()
+            .set_title("Select folder to export Markdown into")
+            .set_directory(&self.cwd)
+            .pick_folder();
+
+        let Some(export_folder) = export_folder else {
+            return Ok(None);
+        };
+
+        let date = chrono::Local::now().date_naive();
+        let export_path = export_folder.join(format!("{}_{}.md", date, self.group.id));
+        self.export_to(&export_path)?;
+        Ok(Some(export_path))
+    }
+
+    /// Exports into the given file without asking, e.g. to repeat an earlier export.
+    pub fn export_to(&self, export_path: &std::path::Path) -> AnyResult<()> {
+        let export_folder = export_path
+            .parent()
+            .context("Export path has no parent folder.")?;
+        let image_paths = crate::export_common::copy_group_images(
+            &self.cwd,
+            export_folder,
+            self.group.id,
+            &self.entries,
+            &self.image_options,
+        )?;
+
+        let mut result = String::with_capacity(10_000);
+        if self.front_matter {
+            result.push_str(&self.create_front_matter()?);
+        }
+        writeln!(result, "# {}\n", self.title())?;
+        for (manga, images) in &self.entries {
+            result.push_str(&Self::create_entry_element(manga, images, &image_paths)?);
+        }
+
+        std::fs::write(export_path, result)
+            .with_context(|| format!("Failed to write '{}'.", export_path.display()))?;
+        Ok(())
+    }
+}
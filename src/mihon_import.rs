@@ -0,0 +1,107 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+use crate::list_import::{ImportedEntry, ListImport, ListScoreScale};
+
+// Subset of Mihon's backup schema (`eu.kanade.tachiyomi.data.backup.models`),
+// fields that aren't needed for the import are skipped by prost.
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct Backup {
+    #[prost(message, repeated, tag = "1")]
+    backup_manga: Vec<BackupManga>,
+    #[prost(message, repeated, tag = "2")]
+    backup_categories: Vec<BackupCategory>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct BackupManga {
+    #[prost(string, tag = "3")]
+    title: String,
+    /// Orders of the categories the manga belongs to
+    #[prost(int64, repeated, tag = "17")]
+    categories: Vec<i64>,
+    /// Missing in older backups, which only had library manga
+    #[prost(bool, optional, tag = "100")]
+    favorite: Option<bool>,
+    #[prost(message, repeated, tag = "104")]
+    history: Vec<BackupHistory>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct BackupCategory {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(int64, tag = "2")]
+    order: i64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct BackupHistory {
+    /// Milliseconds since the Unix epoch
+    #[prost(int64, tag = "2")]
+    last_read: i64,
+}
+
+/// Reads a Mihon/Tachiyomi `.tachibk` (or older `.proto.gz`) backup: gzipped protobuf.
+pub fn read_backup_file(path: &Path) -> AnyResult<ListImport> {
+    let contents =
+        std::fs::read(path).with_context(|| format!("Failed to read '{}'.", path.display()))?;
+
+    let mut unpacked = Vec::with_capacity(contents.len() * 8);
+    flate2::read::GzDecoder::new(contents.as_slice())
+        .read_to_end(&mut unpacked)
+        .context("Failed to unpack backup, is it a Mihon backup file?")?;
+    let backup = <Backup as prost::Message>::decode(unpacked.as_slice())
+        .context("Failed to decode backup, is it a Mihon backup file?")?;
+
+    let categories: HashMap<i64, String> = backup
+        .backup_categories
+        .into_iter()
+        .map(|x| (x.order, x.name))
+        .collect();
+
+    let mut entries: Vec<ImportedEntry> = backup
+        .backup_manga
+        .into_iter()
+        // Manga that were only browsed or read outside the library
+        .filter(|manga| manga.favorite != Some(false))
+        .map(|manga| ImportedEntry {
+            name: manga.title.trim().to_string(),
+            list_score: 0.,
+            comment: String::new(),
+            categories: manga
+                .categories
+                .iter()
+                .filter_map(|x| categories.get(x).cloned())
+                .collect(),
+            last_read: manga
+                .history
+                .iter()
+                .map(|x| x.last_read)
+                .filter(|x| *x > 0)
+                .max()
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .map(|x| x.with_timezone(&chrono::Local).naive_local()),
+            selected: true,
+        })
+        .collect();
+    if entries.is_empty() {
+        anyhow::bail!("No library manga found in '{}'.", path.display());
+    }
+    entries.sort_by_key(|x| std::cmp::Reverse(x.last_read));
+
+    Ok(ListImport {
+        source: path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+        entries,
+        scale: ListScoreScale::Ten,
+    })
+}
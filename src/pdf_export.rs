@@ -0,0 +1,319 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+use printpdf::{
+    ColorBits, ColorSpace, Image, ImageFilter, ImageTransform, ImageXObject, IndirectFontRef, Mm,
+    PdfDocument, PdfDocumentReference, PdfLayerReference, PdfPageIndex, Px,
+};
+
+use shared::types::{MangaEntry, MangaGroup, MangaImage};
+
+const PAGE_WIDTH: f32 = 210.;
+const PAGE_HEIGHT: f32 = 297.;
+const PAGE_MARGIN: f32 = 15.;
+const MM_PER_POINT: f32 = 25.4 / 72.;
+/// Images are embedded at this resolution, i.e. at most about 135mm across on the page.
+const IMAGE_MAX_DIMENSION: u32 = 1600;
+const IMAGE_DPI: f32 = 300.;
+const IMAGE_GAP: f32 = 4.;
+/// Height of image rows relative to their cell width, about the shape of a manga page.
+const IMAGE_ASPECT: f32 = 1.45;
+/// Rows that would be shorter than this in the space left go onto a continuation page.
+const MIN_ROW_HEIGHT: f32 = 60.;
+const TEXT_WIDTH: f32 = PAGE_WIDTH - 2. * PAGE_MARGIN;
+
+/// Font used both for drawing into the PDF and for measuring text when wrapping it.
+struct PdfFont<'a> {
+    reference: IndirectFontRef,
+    metrics: ab_glyph::FontRef<'a>,
+}
+
+impl PdfFont<'_> {
+    fn char_width(&self, c: char, size: f32) -> f32 {
+        use ab_glyph::Font;
+
+        let advance = self.metrics.h_advance_unscaled(self.metrics.glyph_id(c));
+        let units_per_em = self.metrics.units_per_em().unwrap_or(1000.);
+        advance / units_per_em * size * MM_PER_POINT
+    }
+
+    fn text_width(&self, text: &str, size: f32) -> f32 {
+        text.chars().map(|c| self.char_width(c, size)).sum()
+    }
+
+    /// Breaks text into lines no wider than `max_width` millimeters, preferring to break
+    /// at spaces; text without spaces (e.g. Japanese) is broken between any characters.
+    fn wrap_text(&self, text: &str, size: f32, max_width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.lines() {
+            let mut line = String::new();
+            let mut width = 0.;
+            for c in paragraph.chars() {
+                let char_width = self.char_width(c, size);
+                if width + char_width > max_width && !line.is_empty() {
+                    match line.rfind(' ') {
+                        Some(position) if c != ' ' => {
+                            let rest = line.split_off(position + 1);
+                            lines.push(line.trim_end().to_string());
+                            line = rest;
+                        }
+                        _ => lines.push(std::mem::take(&mut line).trim_end().to_string()),
+                    }
+                    width = self.text_width(&line, size);
+                    if c == ' ' {
+                        continue;
+                    }
+                }
+                line.push(c);
+                width += char_width;
+            }
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+/// Current page and the position on it, content that doesn't fit goes onto a new page.
+struct PageCursor<'a> {
+    document: &'a PdfDocumentReference,
+    font: &'a PdfFont<'a>,
+    layer: PdfLayerReference,
+    position: f32,
+    /// Heading written at the top of continuation pages.
+    heading: Option<String>,
+}
+
+impl<'a> PageCursor<'a> {
+    fn new(document: &'a PdfDocumentReference, font: &'a PdfFont<'a>) -> (PdfPageIndex, Self) {
+        let (page, layer) = document.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        let cursor = Self {
+            document,
+            font,
+            layer: document.get_page(page).get_layer(layer),
+            position: PAGE_HEIGHT - PAGE_MARGIN,
+            heading: None,
+        };
+        (page, cursor)
+    }
+
+    fn new_page(&mut self) {
+        let (_, mut cursor) = Self::new(self.document, self.font);
+        if let Some(heading) = &self.heading {
+            for line in self.font.wrap_text(heading, 14., TEXT_WIDTH) {
+                cursor.write_line(&line, 14.);
+            }
+            cursor.position -= 2.;
+        }
+        self.layer = cursor.layer;
+        self.position = cursor.position;
+    }
+
+    fn remaining(&self) -> f32 {
+        self.position - PAGE_MARGIN
+    }
+
+    fn write_line(&mut self, line: &str, size: f32) {
+        self.position -= size * MM_PER_POINT * 1.4;
+        self.layer.use_text(
+            line,
+            size,
+            Mm(PAGE_MARGIN),
+            Mm(self.position),
+            &self.font.reference,
+        );
+    }
+
+    /// Writes lines below the current position, continuing on new pages as needed.
+    fn draw_lines(&mut self, lines: &[String], size: f32) {
+        let line_height = size * MM_PER_POINT * 1.4;
+        for line in lines {
+            if self.remaining() < line_height {
+                self.new_page();
+            }
+            self.write_line(line, size);
+        }
+    }
+}
+
+pub struct PdfExporter {
+    group: MangaGroup,
+    entries: Vec<(MangaEntry, Vec<MangaImage>)>,
+    cwd: std::path::PathBuf,
+}
+
+impl PdfExporter {
+    pub fn new(
+        group: MangaGroup,
+        mut entries: Vec<(MangaEntry, Vec<MangaImage>)>,
+    ) -> AnyResult<Self> {
+        crate::export_common::sort_entries(&mut entries);
+
+        Ok(Self {
+            group,
+            entries,
+            cwd: std::env::current_dir().context("Unable to get CWD.")?,
+        })
+    }
+
+    fn title(&self) -> String {
+        format!("Manga review #{} ({})", self.group.id, self.group.added_on)
+    }
+
+    fn load_image(&self, image: &MangaImage) -> AnyResult<Image> {
+        let (jpeg_data, width, height) = crate::export_common::load_resized_jpeg(
+            &self.cwd.join(&image.path),
+            IMAGE_MAX_DIMENSION,
+            85,
+        )?;
+
+        Ok(Image::from(ImageXObject {
+            width: Px(width as usize),
+            height: Px(height as usize),
+            color_space: ColorSpace::Rgb,
+            bits_per_component: ColorBits::Bit8,
+            interpolate: true,
+            image_data: jpeg_data,
+            image_filter: Some(ImageFilter::DCT),
+            smask: None,
+            clipping_bbox: None,
+        }))
+    }
+
+    /// Lays images out in a grid below the current position, each image scaled to fit
+    /// its cell. Rows that don't fit in the space left start a continuation page.
+    fn draw_image_grid(&self, cursor: &mut PageCursor, images: &[MangaImage]) -> AnyResult<()> {
+        let columns = match images.len() {
+            1 => 1,
+            2..=4 => 2,
+            _ => 3,
+        };
+        #[allow(clippy::cast_precision_loss)]
+        let cell_width = (TEXT_WIDTH - IMAGE_GAP * (columns - 1) as f32) / columns as f32;
+        let full_height = cell_width * IMAGE_ASPECT;
+
+        for row in images.chunks(columns) {
+            if cursor.remaining() - IMAGE_GAP < full_height.min(MIN_ROW_HEIGHT) {
+                cursor.new_page();
+            }
+            let cell_height = full_height.min(cursor.remaining() - IMAGE_GAP);
+            cursor.position -= IMAGE_GAP + cell_height;
+
+            for (column, image) in row.iter().enumerate() {
+                let pdf_image = self.load_image(image)?;
+                #[allow(clippy::cast_precision_loss)]
+                let (natural_width, natural_height) = (
+                    pdf_image.image.width.0 as f32 / IMAGE_DPI * 25.4,
+                    pdf_image.image.height.0 as f32 / IMAGE_DPI * 25.4,
+                );
+                let scale = (cell_width / natural_width).min(cell_height / natural_height);
+
+                #[allow(clippy::cast_precision_loss)]
+                let cell_left = PAGE_MARGIN + column as f32 * (cell_width + IMAGE_GAP);
+
+                pdf_image.add_to_layer(
+                    cursor.layer.clone(),
+                    ImageTransform {
+                        translate_x: Some(
+                            Mm(cell_left + (cell_width - natural_width * scale) / 2.),
+                        ),
+                        translate_y: Some(Mm(
+                            cursor.position + (cell_height - natural_height * scale) / 2.
+                        )),
+                        scale_x: Some(scale),
+                        scale_y: Some(scale),
+                        dpi: Some(IMAGE_DPI),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw_title_page(&self, document: &PdfDocumentReference, font: &PdfFont) {
+        let (_, mut cursor) = PageCursor::new(document, font);
+        cursor.position = PAGE_HEIGHT / 2. + 20.;
+        cursor.draw_lines(&font.wrap_text(&self.title(), 24., TEXT_WIDTH), 24.);
+
+        let scores: Vec<i64> = self.entries.iter().map(|(entry, _)| entry.score).collect();
+        #[allow(clippy::cast_precision_loss)]
+        let average_score = if scores.is_empty() {
+            0.
+        } else {
+            scores.iter().sum::<i64>() as f64 / scores.len() as f64
+        };
+        cursor.position -= 5.;
+        cursor.draw_lines(
+            &[
+                format!("{} entries", self.entries.len()),
+                format!("Average score: {average_score:.1}/10"),
+            ],
+            14.,
+        );
+    }
+
+    fn draw_entry(
+        &self,
+        document: &PdfDocumentReference,
+        font: &PdfFont,
+        manga: &MangaEntry,
+        images: &[MangaImage],
+    ) -> AnyResult<PdfPageIndex> {
+        let (page, mut cursor) = PageCursor::new(document, font);
+        cursor.heading = Some(format!("{} (continued)", manga.name.trim()));
+
+        cursor.draw_lines(&font.wrap_text(manga.name.trim(), 20., TEXT_WIDTH), 20.);
+        cursor.draw_lines(&[format!("Score: {}/10", manga.score)], 14.);
+        if !manga.comment.trim().is_empty() {
+            cursor.position -= 2.;
+            cursor.draw_lines(&font.wrap_text(manga.comment.trim(), 11., TEXT_WIDTH), 11.);
+        }
+
+        cursor.position -= 2.;
+        self.draw_image_grid(&mut cursor, images)?;
+
+        Ok(page)
+    }
+
+    pub fn export_group(&self) -> AnyResult<Option<std::path::PathBuf>> {
+        let date = chrono::Local::now().date_naive();
+
+        let export_path = rfd::FileDialog::new()
+            .set_title("Select PDF export destination")
+            .set_directory(&self.cwd)
+            .add_filter("PDF file", &["pdf"])
+            .set_file_name(format!("{}_{}.pdf", date, self.group.id))
+            .save_file();
+
+        let Some(export_path) = export_path else {
+            return Ok(None);
+        };
+        self.export_to(&export_path)?;
+        Ok(Some(export_path))
+    }
+
+    /// Exports into the given file without asking, e.g. to repeat an earlier export.
+    pub fn export_to(&self, export_path: &std::path::Path) -> AnyResult<()> {
+        let font_data = crate::fonts::load_export_font()?;
+        let document = PdfDocument::empty(self.title());
+        let font = PdfFont {
+            reference: document
+                .add_external_font(font_data.as_slice())
+                .context("Failed to embed export font.")?,
+            metrics: ab_glyph::FontRef::try_from_slice(&font_data)?,
+        };
+
+        self.draw_title_page(&document, &font);
+        for (manga, images) in &self.entries {
+            let page = self.draw_entry(&document, &font, manga, images)?;
+            document.add_bookmark(manga.name.trim(), page);
+        }
+
+        let file = std::fs::File::create(export_path)
+            .with_context(|| format!("Failed to create '{}'.", export_path.display()))?;
+        document
+            .save(&mut std::io::BufWriter::new(file))
+            .context("Failed to write PDF.")?;
+        Ok(())
+    }
+}
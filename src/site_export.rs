@@ -0,0 +1,276 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::export_common::ExportImageOptions;
+use shared::types::{MangaEntry, MangaGroup, MangaImage};
+
+type GroupEntries = Vec<(MangaEntry, Vec<MangaImage>)>;
+
+static INDEX_TEMPLATE: &str = include_str!("site_index.html");
+static GROUP_TEMPLATE: &str = include_str!("site_group.html");
+static TITLE_TEMPLATE: &str = include_str!("site_title.html");
+
+static STYLE_ELEMENT: &str = r"
+<style>
+    body { font-family: sans-serif; max-width: 1100px; margin: 0 auto; padding: 1em; }
+    a { color: #2a6ebb; }
+    table { border-collapse: collapse; width: 100%; }
+    th, td { text-align: left; vertical-align: top; padding: 0.4em; border-bottom: 1px solid #ddd; }
+    article { border-bottom: 1px solid #ddd; padding-bottom: 1em; }
+    .muted { color: #777; }
+    .score { padding: 0.1em 0.4em; border-radius: 0.3em; font-weight: bold; }
+    .images img { max-height: 300px; max-width: 100%; margin: 0.2em; }
+    .images.large img { max-height: 90vh; }
+</style>
+";
+
+/// How the page of each group looks in the generated site.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SiteLayout {
+    /// Output of the selected slideshow template, same as a single group export.
+    /// The reveal.js `dist` and `plugin` folders have to be copied next to `index.html`.
+    Slideshow,
+    /// Plain page with all entries one after another.
+    Article,
+}
+
+#[derive(Debug, Serialize)]
+struct SiteIndexData {
+    title: String,
+    generated_on: String,
+    group_count: usize,
+    groups: Vec<SiteGroupData>,
+}
+
+#[derive(Debug, Serialize)]
+struct SiteGroupData {
+    id: i64,
+    title: String,
+    added_on: String,
+    /// Path of the group page, relative to the site root.
+    page: String,
+    entry_count: usize,
+    average_score_rounded: i64,
+    average_score_text: String,
+    entries: Vec<SiteEntryData>,
+}
+
+#[derive(Debug, Serialize)]
+struct SiteEntryData {
+    name: String,
+    score: i64,
+    comment: String,
+    /// Path of the title page, relative to the site root.
+    page: String,
+    /// Paths of the images, relative to the site root.
+    images: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SiteTitleData<'a> {
+    name: &'a str,
+    score: i64,
+    comment: &'a str,
+    group_title: &'a str,
+    group_page: &'a str,
+    /// Title pages are one folder deep, so every link below is relative to `titles/`.
+    images: Vec<String>,
+    previous_page: Option<String>,
+    next_page: Option<String>,
+}
+
+/// Generates a static site for the whole library:
+///
+/// ```text
+/// index.html                  all groups with dates and average scores
+/// review_{group_id}.html      page of each group
+/// titles/{entry_id}.html      page of each entry
+/// media/review_{group_id}/    images, shared by all pages
+/// ```
+///
+/// All links are relative, so the folder can be uploaded anywhere or opened from disk.
+pub struct SiteExporter<'a> {
+    groups: Vec<(MangaGroup, GroupEntries)>,
+    layout: SiteLayout,
+    template_name: String,
+    image_options: ExportImageOptions,
+    handlebars: handlebars::Handlebars<'a>,
+    cwd: std::path::PathBuf,
+}
+
+impl SiteExporter<'_> {
+    pub fn new(
+        mut groups: Vec<(MangaGroup, GroupEntries)>,
+        layout: SiteLayout,
+        template_name: &str,
+        image_options: ExportImageOptions,
+    ) -> AnyResult<Self> {
+        let cwd = std::env::current_dir().context("Unable to get CWD.")?;
+
+        let mut handlebars = handlebars::Handlebars::new();
+        handlebars.set_strict_mode(true);
+        crate::export_helpers::register_helpers(&mut handlebars, &cwd)?;
+        handlebars.register_partial("site_style", STYLE_ELEMENT)?;
+        handlebars.register_template_string("index", INDEX_TEMPLATE)?;
+        handlebars.register_template_string("group", GROUP_TEMPLATE)?;
+        handlebars.register_template_string("title", TITLE_TEMPLATE)?;
+
+        for (_, entries) in &mut groups {
+            crate::export_common::sort_entries(entries);
+        }
+
+        Ok(Self {
+            groups,
+            layout,
+            template_name: template_name.to_string(),
+            image_options,
+            handlebars,
+            cwd,
+        })
+    }
+
+    fn group_title(group: &MangaGroup) -> String {
+        format!("Manga review #{} ({})", group.id, group.added_on)
+    }
+
+    fn group_page(group: &MangaGroup) -> String {
+        format!("review_{}.html", group.id)
+    }
+
+    fn title_page(entry: &MangaEntry) -> String {
+        format!("titles/{}.html", entry.id)
+    }
+
+    fn create_group_data(
+        group: &MangaGroup,
+        entries: &[(MangaEntry, Vec<MangaImage>)],
+        image_paths: &HashMap<i64, String>,
+    ) -> SiteGroupData {
+        #[allow(clippy::cast_precision_loss)]
+        let average_score = if entries.is_empty() {
+            0.
+        } else {
+            entries.iter().map(|(entry, _)| entry.score).sum::<i64>() as f64 / entries.len() as f64
+        };
+
+        SiteGroupData {
+            id: group.id,
+            title: Self::group_title(group),
+            added_on: group.added_on.to_string(),
+            page: Self::group_page(group),
+            entry_count: entries.len(),
+            #[allow(clippy::cast_possible_truncation)]
+            average_score_rounded: average_score.round() as i64,
+            average_score_text: format!("{average_score:.1}"),
+            entries: entries
+                .iter()
+                .map(|(manga, images)| SiteEntryData {
+                    name: manga.name.clone(),
+                    score: manga.score,
+                    comment: manga.comment.clone(),
+                    page: Self::title_page(manga),
+                    images: images.iter().map(|x| image_paths[&x.id].clone()).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    fn write_page(path: &Path, contents: &str) -> AnyResult<()> {
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write '{}'.", path.display()))
+    }
+
+    fn write_title_pages(&self, site_folder: &Path, group: &SiteGroupData) -> AnyResult<()> {
+        // Entry pages link to each other from inside `titles/`
+        let sibling_page = |index: usize| {
+            group
+                .entries
+                .get(index)
+                .map(|x| x.page.trim_start_matches("titles/").to_string())
+        };
+
+        for (index, entry) in group.entries.iter().enumerate() {
+            let data = SiteTitleData {
+                name: &entry.name,
+                score: entry.score,
+                comment: &entry.comment,
+                group_title: &group.title,
+                group_page: &group.page,
+                images: entry.images.iter().map(|x| format!("../{x}")).collect(),
+                previous_page: index.checked_sub(1).and_then(sibling_page),
+                next_page: sibling_page(index + 1),
+            };
+            let page = self
+                .handlebars
+                .render("title", &data)
+                .with_context(|| format!("Failed to render page of '{}'.", entry.name))?;
+            Self::write_page(&site_folder.join(&entry.page), &page)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn export_site(&self) -> AnyResult<Option<std::path::PathBuf>> {
+        let site_folder = rfd::FileDialog::new()
+            .set_title("Select website folder")
+            .set_directory(&self.cwd)
+            .pick_folder();
+
+        let Some(site_folder) = site_folder else {
+            return Ok(None);
+        };
+        self.export_to(&site_folder)?;
+        Ok(Some(site_folder))
+    }
+
+    /// Writes the site into the given folder without asking, e.g. to repeat an earlier export.
+    pub fn export_to(&self, site_folder: &std::path::Path) -> AnyResult<()> {
+        std::fs::create_dir_all(site_folder.join("titles"))?;
+
+        let mut groups = Vec::with_capacity(self.groups.len());
+        for (group, entries) in &self.groups {
+            let image_paths = crate::export_common::copy_group_images(
+                &self.cwd,
+                site_folder,
+                group.id,
+                entries,
+                &self.image_options,
+            )?;
+            let group_data = Self::create_group_data(group, entries, &image_paths);
+
+            let group_page = match self.layout {
+                SiteLayout::Slideshow => crate::manga_group_export::MangaGroupExporter::new(
+                    group.clone(),
+                    entries.clone(),
+                    &self.template_name,
+                    self.image_options,
+                )?
+                .render(&image_paths)?,
+                SiteLayout::Article => self
+                    .handlebars
+                    .render("group", &group_data)
+                    .with_context(|| format!("Failed to render page of group #{}.", group.id))?,
+            };
+            Self::write_page(&site_folder.join(&group_data.page), &group_page)?;
+            self.write_title_pages(site_folder, &group_data)?;
+
+            groups.push(group_data);
+        }
+
+        let index = SiteIndexData {
+            title: "Manga reviews".to_string(),
+            generated_on: chrono::Local::now().date_naive().to_string(),
+            group_count: groups.len(),
+            groups,
+        };
+        let page = self
+            .handlebars
+            .render("index", &index)
+            .context("Failed to render the index page.")?;
+        Self::write_page(&site_folder.join("index.html"), &page)
+    }
+}
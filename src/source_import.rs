@@ -0,0 +1,135 @@
+use anyhow::Context;
+use anyhow::Result as AnyResult;
+
+use std::path::{Path, PathBuf};
+
+use crate::image_ingest::IngestImage;
+
+/// Which pages of a chapter get attached to its entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageSampling {
+    First,
+    Random,
+    EvenlySpaced,
+}
+
+impl PageSampling {
+    pub const ALL: [Self; 3] = [Self::First, Self::Random, Self::EvenlySpaced];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::First => "First pages",
+            Self::Random => "Random pages",
+            Self::EvenlySpaced => "Evenly spaced",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SourceImportOptions {
+    pub page_count: usize,
+    pub sampling: PageSampling,
+}
+
+impl Default for SourceImportOptions {
+    fn default() -> Self {
+        Self {
+            page_count: 3,
+            sampling: PageSampling::EvenlySpaced,
+        }
+    }
+}
+
+fn is_archive(path: &Path) -> bool {
+    path.is_file()
+        && path.extension().is_some_and(|ext| {
+            crate::archive_import::ARCHIVE_EXTENSIONS
+                .iter()
+                .any(|x| ext.eq_ignore_ascii_case(x))
+        })
+}
+
+/// Subfolders and archives of `folder` with the entry names made of them, in natural order.
+pub fn find_sources(folder: &Path) -> AnyResult<Vec<(String, PathBuf)>> {
+    let mut result = std::fs::read_dir(folder)
+        .with_context(|| format!("Failed to read folder '{}'.", folder.display()))?
+        .filter_map(Result::ok)
+        .map(|x| x.path())
+        .filter_map(|path| {
+            let name = if path.is_dir() {
+                path.file_name()?
+            } else if is_archive(&path) {
+                path.file_stem()?
+            } else {
+                return None;
+            };
+            Some((name.to_string_lossy().to_string(), path))
+        })
+        .collect::<Vec<_>>();
+    result.sort_by(|a, b| natord::compare(&a.0, &b.0));
+    Ok(result)
+}
+
+/// Names of the pages of a chapter folder or archive, in natural order.
+pub fn list_pages(source: &Path) -> AnyResult<Vec<String>> {
+    if is_archive(source) {
+        return crate::archive_import::list_pages(source);
+    }
+
+    let mut names = std::fs::read_dir(source)
+        .with_context(|| format!("Failed to read folder '{}'.", source.display()))?
+        .filter_map(Result::ok)
+        .map(|x| x.path())
+        .filter(|x| {
+            x.is_file() && image::ImageFormat::from_path(x).is_ok_and(|x| x.reading_enabled())
+        })
+        .map(|x| x.file_name().unwrap().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    names.sort_by(|a, b| natord::compare(a, b));
+    Ok(names)
+}
+
+/// Picks up to `page_count` pages, keeping them in reading order.
+pub fn sample_pages(pages: &[String], options: SourceImportOptions) -> Vec<String> {
+    let count = options.page_count.min(pages.len());
+    let mut indexes = match options.sampling {
+        PageSampling::First => (0..count).collect::<Vec<_>>(),
+        PageSampling::Random => {
+            rand::seq::index::sample(&mut rand::rng(), pages.len(), count).into_vec()
+        }
+        // First and last pages included, when there is more than one
+        PageSampling::EvenlySpaced if count > 1 => (0..count)
+            .map(|x| x * (pages.len() - 1) / (count - 1))
+            .collect(),
+        PageSampling::EvenlySpaced => (0..count).collect(),
+    };
+    indexes.sort_unstable();
+    indexes.dedup();
+    indexes.into_iter().map(|x| pages[x].clone()).collect()
+}
+
+/// Reads pages of a chapter folder or archive, along with their names.
+pub fn read_pages(source: &Path, names: &[String]) -> AnyResult<Vec<(String, IngestImage)>> {
+    if is_archive(source) {
+        return crate::archive_import::read_pages(source, names);
+    }
+
+    names
+        .iter()
+        .map(|name| {
+            let path = source.join(name);
+            let contents = std::fs::read(&path)
+                .with_context(|| format!("Failed to open '{}'.", path.display()))?;
+            Ok((name.clone(), IngestImage::file(contents, &path)))
+        })
+        .collect()
+}
+
+/// Pages to attach from a chapter folder or archive.
+pub fn load_sample_pages(
+    source: &Path,
+    options: SourceImportOptions,
+) -> AnyResult<Vec<(String, IngestImage)>> {
+    let pages = list_pages(source)?;
+    read_pages(source, &sample_pages(&pages, options))
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+use std::fmt::Write;
+
+use shared::types::{MangaEntry, MangaGroup, MangaImage};
+
+/// Discord doesn't accept messages longer than this many characters.
+pub const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextExportFormat {
+    BBCode,
+    Discord,
+}
+
+impl TextExportFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::BBCode => "BBCode",
+            Self::Discord => "Discord",
+        }
+    }
+
+    fn message_limit(self) -> Option<usize> {
+        match self {
+            Self::BBCode => None,
+            Self::Discord => Some(DISCORD_MESSAGE_LIMIT),
+        }
+    }
+}
+
+pub struct TextExporter {
+    group: MangaGroup,
+    entries: Vec<(MangaEntry, Vec<MangaImage>)>,
+    format: TextExportFormat,
+}
+
+impl TextExporter {
+    pub fn new(
+        group: MangaGroup,
+        mut entries: Vec<(MangaEntry, Vec<MangaImage>)>,
+        format: TextExportFormat,
+    ) -> Self {
+        crate::export_common::sort_entries(&mut entries);
+
+        Self {
+            group,
+            entries,
+            format,
+        }
+    }
+
+    fn create_header(&self) -> String {
+        let title = format!("Manga review #{} ({})", self.group.id, self.group.added_on);
+        match self.format {
+            TextExportFormat::BBCode => format!("[size=150][b]{title}[/b][/size]\n\n"),
+            TextExportFormat::Discord => format!("## {title}\n\n"),
+        }
+    }
+
+    fn create_entry_element(&self, manga: &MangaEntry, images: &[MangaImage]) -> String {
+        let mut result = String::with_capacity(manga.comment.len() + 100);
+        let name = manga.name.trim();
+        let comment = manga.comment.trim();
+        let images_note = match images.len() {
+            0 | 1 => String::new(),
+            count => format!(" ({count} images)"),
+        };
+
+        match self.format {
+            TextExportFormat::BBCode => {
+                writeln!(result, "[b]{name}[/b] — {}/10{images_note}", manga.score).unwrap();
+                if !comment.is_empty() {
+                    writeln!(result, "[quote]{comment}[/quote]").unwrap();
+                }
+            }
+            TextExportFormat::Discord => {
+                writeln!(result, "**{name}** — {}/10{images_note}", manga.score).unwrap();
+                for line in comment.lines() {
+                    writeln!(result, "> {line}").unwrap();
+                }
+            }
+        }
+        result.push('\n');
+        result
+    }
+
+    /// Renders the group into one or more messages, each fitting into the
+    /// format's message limit.
+    pub fn render(&self) -> Vec<String> {
+        let mut blocks = Vec::with_capacity(self.entries.len() + 1);
+        blocks.push(self.create_header());
+        for (manga, images) in &self.entries {
+            blocks.push(self.create_entry_element(manga, images));
+        }
+
+        match self.format.message_limit() {
+            Some(limit) => split_into_messages(&blocks, limit),
+            None => vec![blocks.concat().trim_end().to_string()],
+        }
+    }
+}
+
+/// Packs blocks into as few messages as possible without splitting a block,
+/// unless the block is longer than a message by itself; then it's split
+/// by lines, and overly long lines by characters.
+fn split_into_messages(blocks: &[String], limit: usize) -> Vec<String> {
+    let mut pieces = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        if block.trim_end().chars().count() <= limit {
+            pieces.push(block.clone());
+            continue;
+        }
+        for line in block.split_inclusive('\n') {
+            let chars: Vec<char> = line.chars().collect();
+            for chunk in chars.chunks(limit) {
+                pieces.push(chunk.iter().collect());
+            }
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut current = String::new();
+    for piece in &pieces {
+        // Leftover line breaks would otherwise start the next message and push it over
+        if current.trim().is_empty() {
+            current.clear();
+        }
+        let mut piece = if current.is_empty() {
+            piece.trim_start_matches('\n')
+        } else {
+            piece
+        };
+        let new_length = current.chars().count() + piece.trim_end().chars().count();
+        if new_length > limit && !current.is_empty() {
+            result.push(current.trim_end().to_string());
+            current.clear();
+            piece = piece.trim_start_matches('\n');
+        }
+        current.push_str(piece);
+    }
+    if !current.trim().is_empty() {
+        result.push(current.trim_end().to_string());
+    }
+    result
+}
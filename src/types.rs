@@ -1,4 +1,22 @@
-use shared::types::{DisplayedMangaEntry, DisplayedMangaImage, MangaEntry, MangaGroup, MangaImage};
+use std::path::PathBuf;
+
+use crate::archive_import::ArchivePages;
+use crate::csv_transfer::CsvImportChange;
+use crate::export_common::ExportImageOptions;
+use crate::export_lint::{LintAction, LintReport};
+use crate::export_preview::ExportPreview;
+use crate::folder_names::{FolderNameReview, NameCleanupRules};
+use crate::folder_sync::FolderSyncPlan;
+use crate::image_export::ImageExportOptions;
+use crate::image_ingest::IngestSettings;
+use crate::inbox::{InboxItem, InboxSettings};
+use crate::list_import::ListImport;
+use crate::site_export::SiteLayout;
+use crate::source_import::SourceImportOptions;
+use crate::text_export::TextExportFormat;
+use shared::types::{
+    DisplayedMangaEntry, DisplayedMangaImage, MangaEntry, MangaExport, MangaGroup, MangaImage,
+};
 
 #[derive(Debug)]
 // TODO: trim down parameters from struct to a single id?
@@ -16,8 +34,42 @@ pub enum GuiCommand {
     AddImagesFromDisk(MangaEntry),
     UpdateEntryImages(MangaEntry),
     AddImageFromClipboard(MangaEntry),
-    ExportGroup(MangaGroup),
-    AddNamesFromFolder(MangaGroup),
+    AddImagesFromPaths(MangaEntry, Vec<PathBuf>),
+    OpenArchive(MangaEntry),
+    AddImagesFromArchive(MangaEntry, PathBuf, Vec<String>),
+    OpenImageEditor(MangaImage),
+    LoadFullImage(MangaImage),
+    /// Edited pixels, replacing the stored file when `true` or added as a new image.
+    SaveEditedImage(MangaImage, image::RgbaImage, bool),
+    SaveIngestSettings(IngestSettings),
+    SaveInboxSettings(InboxSettings),
+    /// Entry that new inbox files go straight into, instead of the tray.
+    SetInboxTarget(Option<MangaEntry>),
+    AddInboxFiles(MangaEntry, Vec<PathBuf>),
+    ExportGroup(MangaGroup, String, ExportImageOptions),
+    GetExportTemplates,
+    ExportGroupMarkdown(MangaGroup, bool, ExportImageOptions),
+    ExportGroupText(MangaGroup, TextExportFormat),
+    ExportGroupPdf(MangaGroup),
+    ExportEpub(Vec<MangaGroup>),
+    ExportGroupImage(MangaGroup, ImageExportOptions),
+    ExportSite(SiteLayout, String, ExportImageOptions),
+    PreviewExport(MangaGroup, String, ExportImageOptions),
+    RepeatExport(MangaExport),
+    LintGroup(MangaGroup, Vec<MangaEntry>, LintAction),
+    AddNamesFromFolder(MangaGroup, NameCleanupRules, bool),
+    ApplyFolderNameReview(FolderNameReview),
+    SyncGroupFolder(MangaGroup, NameCleanupRules),
+    ApplyFolderSync(FolderSyncPlan),
+    ImportFromSources(MangaGroup, SourceImportOptions),
+    RepickSourcePages(MangaEntry, SourceImportOptions),
+    ExportEntriesCsv(Option<MangaGroup>),
+    PreviewCsvImport,
+    ApplyCsvImport(Vec<CsvImportChange>),
+    PreviewListImport,
+    PreviewMihonImport,
+    ImportHtmlExports,
+    ImportEntries(Option<MangaGroup>, Vec<MangaEntry>),
     Exit,
 }
 
@@ -26,6 +78,28 @@ pub enum BackendCommand {
     UpdateGroups(Vec<MangaGroup>),
     UpdateSelectedGroup(Vec<DisplayedMangaEntry>),
     UpdateThumbnailsForMangaEntry((i64, Vec<DisplayedMangaImage>)),
+    UpdateExportTemplates(Vec<String>),
+    UpdateIngestSettings(IngestSettings),
+    UpdateInboxSettings(InboxSettings),
+    /// Tray contents, replacing the previous ones.
+    ShowInboxItems(Vec<InboxItem>),
+    AddInboxItems(Vec<InboxItem>),
+    RemoveInboxItems(Vec<PathBuf>),
+    ShowError(String),
+    ShowTextExport((TextExportFormat, Vec<String>)),
+    ShowCsvImportPreview(Vec<CsvImportChange>),
+    ShowListImportPreview(ListImport),
+    SelectGroup(MangaGroup),
+    ShowExportPreview(ExportPreview),
+    /// Export history of the group with the given id.
+    UpdateExportHistory(i64, Vec<MangaExport>),
+    ShowLintReport(LintReport),
+    ShowArchivePages(ArchivePages),
+    ShowFolderNameReview(FolderNameReview),
+    ShowFolderSync(FolderSyncPlan),
+    ShowImageEditor(MangaImage, image::RgbaImage),
+    /// Pixels of the image with the given id, for the viewer.
+    ShowFullImage(i64, egui::ColorImage),
 }
 
 pub type SqlitePool = sqlx::Pool<sqlx::sqlite::Sqlite>;
@@ -33,3 +107,6 @@ pub type GuiChannelSend = crossbeam::channel::Sender<GuiCommand>;
 pub type GuiChannelRecv = crossbeam::channel::Receiver<GuiCommand>;
 pub type BackendChannelSend = crossbeam::channel::Sender<BackendCommand>;
 pub type BackendChannelRecv = crossbeam::channel::Receiver<BackendCommand>;
+
+/// Scores that can be set with the slider in the GUI.
+pub const SCORE_RANGE: std::ops::RangeInclusive<i64> = 1..=10;